@@ -1,15 +1,33 @@
 use crate::args_util::args::Args;
+use crate::args_util::in_format::InFormat;
+use crate::args_util::input_file::InputFile;
 use crate::args_util::is_tsv_filename::is_tsv_filename;
 use crate::csv::csv_parser::CsvParser;
-use crate::csv::csv_writer::CsvWriter;
-use crate::db::execute_statements::execute_statements;
+use crate::csv::fixed_width_parser::FixedWidthParser;
+use crate::db::detect_options::DetectOptions;
+use crate::db::execute_statements::{execute_statements, is_query, stream_statement};
 use crate::db::list_available_table::list_available_tables;
-use crate::format::STYLE_BAR;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::statement_result::StatementResult;
+use crate::db::table_loader::TableLoader;
+use crate::db::table_name_from_path::{table_name_from_input, validate_table_name};
+use crate::db::table_schema::TableSchema;
+use crate::json::json_parser::JsonParser;
+use crate::output::result_writer::{
+    create_result_writer, write_all_results, write_statement_result,
+};
+use crate::output::routed_outputs::RoutedOutputs;
+use crate::parquet::parquet_parser::ParquetParser;
+use crate::spreadsheet::spreadsheet_parser::SpreadsheetParser;
 use crate::tui::tui_main::tui_main;
 
 use crate::db::initialize_database::initialize_database;
+use std::collections::HashMap;
 use tracing::{debug, error};
 
+/// Column of `--in-union` tables holding the path of the input file of each row
+const SOURCE_FILE_COLUMN: &str = "_source_file";
+
 /// Main application logic
 pub fn app(args: Args) -> anyhow::Result<()> {
     debug!("Starting application with arguments: {:?}", args);
@@ -17,19 +35,37 @@ pub fn app(args: Args) -> anyhow::Result<()> {
     // Initialize SQLite database connection
     let pool = initialize_database(&args.out_database)?;
 
-    // Create CSV parser based on input arguments
-    let parser = CsvParser::new(&args);
+    // Create parsers based on input arguments
+    let parsers = Parsers {
+        csv: CsvParser::new(&args),
+        fixed_width: FixedWidthParser::new(&args),
+        json: JsonParser::new(&args),
+        spreadsheet: SpreadsheetParser::new(&args),
+        parquet: ParquetParser::new(&args),
+    };
 
     // Process data from standard input if provided
-    process_stdin(&parser, &pool, args.in_delimiter)?;
+    process_stdin(&parsers, &pool, &args)?;
 
     // Process data from input files
-    process_input_files(&parser, &pool, &args.in_file, args.in_delimiter)?;
+    process_input_files(&parsers, &pool, &args)?;
 
     // Ensure there are tables in the database
     let available_table = list_available_tables(&pool)?;
     if available_table.is_empty() {
-        return Err(anyhow::anyhow!("No valid CSV data inputted. Specify files with --in-file or pass content through STDIN."));
+        return Err(anyhow::anyhow!(
+            "No valid CSV data inputted. Specify files with --in-file or pass content through STDIN."
+        ));
+    }
+
+    // Overrides for a table that was not imported, e.g. a misspelled name, would be ignored
+    let unused_tables = args.in_schema.unused_tables(&available_table);
+    if !unused_tables.is_empty() {
+        return Err(anyhow::anyhow!(
+            "--in-schema or --in-type names tables that are not imported: \"{}\". Available tables: \"{}\"",
+            unused_tables.join("\", \""),
+            available_table.join("\", \"")
+        ));
     }
 
     // Execute SQL statements or start interactive mode
@@ -46,62 +82,121 @@ pub fn app(args: Args) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Process CSV data from standard input
+/// Parsers of the input formats
+struct Parsers<'a> {
+    csv: CsvParser<'a>,
+    fixed_width: FixedWidthParser<'a>,
+    json: JsonParser<'a>,
+    spreadsheet: SpreadsheetParser<'a>,
+    parquet: ParquetParser<'a>,
+}
+
+/// Process CSV data from standard input, or fixed-width data with a column layout
 fn process_stdin(
-    parser: &CsvParser,
+    parsers: &Parsers,
     pool: &r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>,
-    delimiter: u8,
+    args: &Args,
 ) -> anyhow::Result<()> {
     let conn = pool.get()?;
-    
+
     debug!("Processing CSV data from stdin.");
-    let (buf_name, statements) = parser.parse_stdin(None, delimiter)?;
-
-    let statements_len = statements.len();
-    let pb = indicatif::ProgressBar::new(statements_len as u64);
-    pb.set_style(STYLE_BAR.clone());
-    pb.set_message(format!("Importing data from {}", buf_name));
-    for (index, statement) in statements.iter().enumerate().take(statements_len) {
-        if index % 10 == 0 {
-            pb.set_position(index as u64);
-        }
-        conn.execute(statement, ())?;
+    let result = if args.in_fixed_layout.is_some() {
+        parsers.fixed_width.parse_stdin(&conn)?
+    } else {
+        parsers.csv.parse_stdin(&conn, None, args.in_delimiter)?
+    };
+    if let Some(buf_name) = result {
+        debug!("Imported data from stdin into table: {}", buf_name);
     }
     Ok(())
 }
 
-/// Process CSV data from specified input files
+/// Process CSV, fixed-width, JSON, spreadsheet or Parquet data from specified input files
+///
+/// Table names are given as `name=path` or derived from the path, with the
+/// sheet name for sheets of a workbook. An error is
+/// returned before importing if two inputs, or an input and an existing table,
+/// would get the same name. With `--in-union`, all files go into one table.
 fn process_input_files(
-    parser: &CsvParser,
+    parsers: &Parsers,
     pool: &r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>,
-    input_files: &[String],
-    default_delimiter: u8,
+    args: &Args,
 ) -> anyhow::Result<()> {
-    let conn = pool.get()?;
+    // SQLite table names are case-insensitive
+    let mut table_sources: HashMap<String, String> = list_available_tables(pool)?
+        .into_iter()
+        .map(|table_name| {
+            let source = if table_name == "stdin" {
+                "STDIN".to_string()
+            } else {
+                "an existing table in the database".to_string()
+            };
+            (table_name.to_lowercase(), source)
+        })
+        .collect();
+
+    if let Some(union_table) = &args.in_union {
+        validate_table_name(union_table)?;
+        if let Some(source) = table_sources.get(&union_table.to_lowercase()) {
+            return Err(anyhow::anyhow!(
+                "Table name \"{}\" of --in-union collides with {}.",
+                union_table,
+                source
+            ));
+        }
+        return process_union(parsers, pool, args, union_table);
+    }
 
-    for input_file in input_files {
-        let delimiter = if is_tsv_filename(input_file) {
-            b'\t'
-        } else {
-            default_delimiter
+    let mut table_names: Vec<String> = vec![];
+    for input_file in &args.in_file {
+        let table_name = match &input_file.table_name {
+            Some(table_name) => {
+                validate_table_name(table_name)?;
+                table_name.to_string()
+            }
+            None => table_name_from_input(input_file, args.in_table_name_style)?,
         };
+        if let Some(source) = table_sources.get(&table_name.to_lowercase()) {
+            return Err(anyhow::anyhow!(
+                "Table name \"{}\" of {} collides with {}. Give another name as -i name=path or change --in-table-name-style.",
+                table_name,
+                input_file,
+                source
+            ));
+        }
+        table_sources.insert(table_name.to_lowercase(), input_file.to_string());
+        table_names.push(table_name);
+    }
+
+    let conn = pool.get()?;
+
+    for (input_file, table_name) in args.in_file.iter().zip(&table_names) {
+        let in_format = input_format(input_file, args);
+        let delimiter = file_delimiter(input_file.inner_name(), args.in_delimiter);
 
         debug!(
-            "Processing file: {} with delimiter: {}",
-            input_file, delimiter
+            "Processing file: {} as {:?} with delimiter: {}",
+            input_file, in_format, delimiter
         );
-        match parser.parse_file(input_file, None, delimiter) {
-            Ok((buf_name, statements)) => {
-                let statements_len = statements.len();
-                let pb = indicatif::ProgressBar::new(statements_len as u64);
-                pb.set_style(STYLE_BAR.clone());
-                pb.set_message(format!("Importing data from file: {}", buf_name));
-                for (index, statement) in statements.iter().enumerate().take(statements_len) {
-                    if index % 10 == 0 {
-                        pb.set_position(index as u64);
-                    }
-                    conn.execute(statement, ())?;
-                }
+        let result = match in_format {
+            InFormat::Csv => parsers
+                .csv
+                .parse_file(&conn, input_file, table_name, None, delimiter),
+            InFormat::FixedWidth => parsers
+                .fixed_width
+                .parse_file(&conn, input_file, table_name),
+            InFormat::Json => parsers
+                .json
+                .parse_file(&conn, input_file, table_name, false),
+            InFormat::Ndjson => parsers.json.parse_file(&conn, input_file, table_name, true),
+            InFormat::Spreadsheet => parsers
+                .spreadsheet
+                .parse_file(&conn, input_file, table_name),
+            InFormat::Parquet => parsers.parquet.parse_file(&conn, input_file, table_name),
+        };
+        match result {
+            Ok(()) => {
+                debug!("Imported file {} into table: {}", input_file, table_name);
             }
             Err(err) => {
                 error!("Error processing file {}: {}", input_file, err);
@@ -112,25 +207,166 @@ fn process_input_files(
     Ok(())
 }
 
+/// Imports all input files into a single table for `--in-union`
+///
+/// Every file is scanned into one schema first, so columns are matched by name
+/// and types are widened across files. Columns missing from a file are `NULL`,
+/// and the `_source_file` column holds the path each row came from, as
+/// `archive.zip#member` for archive members and `report.xlsx#Sheet1` for sheets.
+fn process_union(
+    parsers: &Parsers,
+    pool: &r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>,
+    args: &Args,
+    table_name: &str,
+) -> anyhow::Result<()> {
+    if args.in_file.is_empty() {
+        return Ok(());
+    }
+
+    // First pass: build one schema from all files
+    let mut schema = TableSchema::new(DetectOptions::from(args), args.in_schema.table(table_name));
+    for input_file in &args.in_file {
+        debug!(
+            "Scanning file {} for union table: {}",
+            input_file, table_name
+        );
+        let delimiter = file_delimiter(input_file.inner_name(), args.in_delimiter);
+        match input_format(input_file, args) {
+            InFormat::Csv => parsers.csv.scan_file(input_file, delimiter, &mut schema)?,
+            InFormat::FixedWidth => parsers.fixed_width.scan_file(input_file, &mut schema)?,
+            InFormat::Json => parsers.json.scan_file(input_file, false, &mut schema)?,
+            InFormat::Ndjson => parsers.json.scan_file(input_file, true, &mut schema)?,
+            InFormat::Spreadsheet => parsers.spreadsheet.scan_file(input_file, &mut schema)?,
+            InFormat::Parquet => parsers.parquet.scan_file(input_file, &mut schema)?,
+        }
+    }
+    schema.finish(table_name)?;
+    let source_index = schema.add_generated_column(SOURCE_FILE_COLUMN, SqliteDataType::Text)?;
+
+    // Second pass: create the table and insert the rows of every file
+    let conn = pool.get()?;
+    let loader = TableLoader::new(&conn, table_name, &args.raw_id, &schema)?;
+    for input_file in &args.in_file {
+        let delimiter = file_delimiter(input_file.inner_name(), args.in_delimiter);
+        let source_name = input_file.to_string();
+        let source = Some((source_index, source_name.as_str()));
+        let row_count = match input_format(input_file, args) {
+            InFormat::Csv => parsers
+                .csv
+                .load_file(input_file, delimiter, &schema, &loader, source)?,
+            InFormat::FixedWidth => parsers
+                .fixed_width
+                .load_file(input_file, &schema, &loader, source)?,
+            InFormat::Json => parsers
+                .json
+                .load_file(input_file, false, &schema, &loader, source)?,
+            InFormat::Ndjson => parsers
+                .json
+                .load_file(input_file, true, &schema, &loader, source)?,
+            InFormat::Spreadsheet => parsers
+                .spreadsheet
+                .load_file(input_file, &schema, &loader, source)?,
+            InFormat::Parquet => parsers
+                .parquet
+                .load_file(input_file, &schema, &loader, source)?,
+        };
+        debug!(
+            "Imported {} rows of {} into union table: {}",
+            row_count, input_file, table_name
+        );
+    }
+    loader.finish()?;
+    Ok(())
+}
+
+/// Returns the format of an input file
+///
+/// With `--in-fixed-widths` or `--in-fixed-layout`, files that would be read as
+/// CSV are read as fixed-width text.
+fn input_format(input_file: &InputFile, args: &Args) -> InFormat {
+    match InFormat::from_filename(input_file.inner_name()) {
+        InFormat::Csv if args.in_fixed_layout.is_some() => InFormat::FixedWidth,
+        in_format => in_format,
+    }
+}
+
+/// Returns the delimiter of a file: tab for `.tsv` files, the given one otherwise
+fn file_delimiter(file_path: &str, default_delimiter: u8) -> u8 {
+    if is_tsv_filename(file_path) {
+        b'\t'
+    } else {
+        default_delimiter
+    }
+}
+
 /// Command-line mode for executing SQL statements
 fn command_line_mode(
     pool: r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>,
     args: Args,
 ) -> anyhow::Result<()> {
-    let query_result =
-        execute_statements(&pool, args.statements.clone(), Some(args.raw_id.clone()))?;
+    // A final query for the default output streams its rows to the writer
+    let mut statements = args.statements.clone();
+    let streamed = match statements.last() {
+        Some(last)
+            if last.output.is_none() && is_query(&last.statement) && !args.out_all_results =>
+        {
+            statements.pop()
+        }
+        _ => None,
+    };
 
-    if let Some(last) = query_result.last() {
-        if args.out_file.is_some() {
-            println!("Saving query results to {}", args.out_file.clone().unwrap());
+    let mut routed_outputs = RoutedOutputs::new(&args);
+    let query_result = execute_statements(
+        &pool,
+        statements,
+        Some(args.raw_id.clone()),
+        Some(&mut routed_outputs),
+    )?;
+    routed_outputs.finish()?;
+
+    if let Some(streamed) = streamed {
+        stream_statement(&pool, &streamed.statement, &args.raw_id, || {
+            if let Some(out_file) = &args.out_file {
+                eprintln!("Saving query results to {}", out_file);
+            }
+            create_result_writer(&args)
+        })?;
+        return Ok(());
+    }
+
+    // Results routed by annotations are written already; once a script routes
+    // results, only the remaining query results go to the default output
+    let routed = query_result
+        .iter()
+        .any(|statement_result| statement_result.output.is_some());
+    let remaining: Vec<&StatementResult> = query_result
+        .iter()
+        .filter(|statement_result| {
+            statement_result.output.is_none() && (statement_result.is_query || !routed)
+        })
+        .collect();
+
+    if args.out_all_results {
+        let query_results: Vec<&StatementResult> = remaining
+            .into_iter()
+            .filter(|statement_result| statement_result.is_query)
+            .collect();
+        if query_results.is_empty() {
+            if routed {
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("No results from the SQL query."));
         }
-        let mut writer = CsvWriter::new(&args)?;
-        if !args.out_without_header {
-            writer.write_record(&last.header)?;
+        return write_all_results(&args, &query_results);
+    }
+
+    if let Some(last) = remaining.last() {
+        if args.out_file.is_some() {
+            eprintln!("Saving query results to {}", args.out_file.clone().unwrap());
         }
-        writer.write_records(&last.rows)?;
-        writer.flush()?;
-    } else {
+        let mut writer = create_result_writer(&args)?;
+        write_statement_result(writer.as_mut(), last)?;
+    } else if !routed {
         return Err(anyhow::anyhow!("No results from the SQL query."));
     }
     Ok(())
@@ -1,5 +1,17 @@
+pub mod args;
+pub mod blob_format;
+pub mod cell_range;
+pub mod compression;
+pub mod date_storage;
+pub mod fixed_layout;
+pub mod in_format;
+pub mod input_file;
 pub mod is_tsv_filename;
+pub mod number_locale;
+pub mod out_format;
 pub mod quote_style;
-pub mod trim;
-pub mod args;
 pub mod raw_args;
+pub mod sql_dialect;
+pub mod sql_script;
+pub mod table_name_style;
+pub mod trim;
@@ -1,4 +1,16 @@
+use crate::args_util::blob_format::BlobFormat;
+use crate::args_util::cell_range::CellRange;
+use crate::args_util::compression::Compression;
+use crate::args_util::date_storage::DateStorage;
+use crate::args_util::fixed_layout::FixedLayout;
+use crate::args_util::input_file::InputFile;
+use crate::args_util::number_locale::NumberLocale;
+use crate::args_util::out_format::OutFormat;
 use crate::args_util::raw_args::RawArgs;
+use crate::args_util::sql_dialect::SqlDialect;
+use crate::args_util::sql_script::{ScriptStatement, parse_script};
+use crate::args_util::table_name_style::TableNameStyle;
+use crate::db::schema_overrides::SchemaOverrides;
 use anyhow::Context;
 use std::io::Read;
 use tracing::debug;
@@ -6,34 +18,58 @@ use tracing::debug;
 /// Represents parsed command-line arguments.
 #[derive(Debug, Clone)]
 pub struct Args {
-    pub in_file: Vec<String>,                       // Input file paths
-    pub statements: Vec<sqlparser::ast::Statement>, // SQL queries
-    pub help: bool,                                 // Display help flag
+    pub in_file: Vec<InputFile>,          // Input files
+    pub statements: Vec<ScriptStatement>, // SQL queries with their outputs
+    pub help: bool,                       // Display help flag
     pub in_allow_leading_zeros: bool,
     pub in_comment: Option<u8>,
-    pub in_delimiter: u8, // CSV delimiter
+    pub in_date_format: Vec<String>, // Date formats replacing the built-in ones
+    pub in_date_storage: DateStorage, // Storage of dates
+    pub in_delimiter: u8,            // CSV delimiter
+    pub in_detect_boolean: bool,     // Enable BOOLEAN detection
+    pub in_detect_dates: bool,       // Enable DATE, DATETIME and TIME detection
     pub in_escape: Option<u8>,
-    pub in_flexible: bool, // Allow varying fields
+    pub in_fixed_bytes: bool, // Fixed-width positions in bytes
+    pub in_fixed_layout: Option<FixedLayout>, // Columns of fixed-width input
+    pub in_flexible: bool,    // Allow varying fields
+    pub in_header_row: Option<u32>, // Header row of spreadsheets, starting at 1
     pub in_no_double_quote: bool,
     pub in_no_header: bool, // No header row
     pub in_no_quoting: bool,
-    pub in_quote: u8,                   // Quote character
-    pub in_terminator: csv::Terminator, // Record terminator
-    pub in_trim: csv::Trim,             // Trimming behavior
+    pub in_null: Vec<String>,                   // Values treated as NULL
+    pub in_number_locale: Option<NumberLocale>, // Grouping and decimal mark
+    pub in_quote: u8,                           // Quote character
+    pub in_range: Option<CellRange>,            // Cells read from spreadsheets
+    pub in_schema: SchemaOverrides,             // Column definitions from --in-schema and --in-type
+    pub in_table_name_style: TableNameStyle,    // Table names derived from paths
+    pub in_terminator: csv::Terminator,         // Record terminator
+    pub in_trim: csv::Trim,                     // Trimming behavior
+    pub in_union: Option<String>,               // Table combining all input files
+    pub out_all_results: bool,                  // Write every query result
+    pub out_blob_format: BlobFormat,            // BLOB encoding
     pub out_comment: Option<u8>,
-    pub out_database: Option<String>, // Output SQLite DB
-    pub out_delimiter: u8,            // Output CSV delimiter
-    pub out_encoding: String,         // Output encoding
+    pub out_compress: Option<Compression>, // Output compression
+    pub out_database: Option<String>,      // Output SQLite DB
+    pub out_delimiter: u8,                 // Output CSV delimiter
+    pub out_encoding: String,              // Output encoding
     pub out_escape: Option<u8>,
-    pub out_file: Option<String>, // Output file path
-    pub out_log: Option<String>,  // Output log path
+    pub out_file: Option<String>,       // Output file path
+    pub out_format: Option<OutFormat>,  // Output format
+    pub out_html_class: Option<String>, // Class of HTML tables
+    pub out_log: Option<String>,        // Output log path
+    pub out_max_width: usize,           // Truncation width of table output
     pub out_no_double_quote: bool,
+    pub out_no_pager: bool,               // Disable the pager for table output
+    pub out_null: String,                 // Text written for NULL
     pub out_quote_style: csv::QuoteStyle, // Output quote style
     pub out_quote: u8,
+    pub out_sql_batch_size: usize,     // Rows per INSERT of SQL output
+    pub out_sql_dialect: SqlDialect,   // Dialect of SQL output
+    pub out_sql_table: Option<String>, // Table name of SQL output
     pub out_terminator: csv::Terminator, // Output terminator
-    pub out_without_header: bool,        // Exclude headers
-    pub raw_id: String,                  // Primary key column
-    pub version: bool,                   // Display version flag
+    pub out_without_header: bool,      // Exclude headers
+    pub raw_id: String,                // Primary key column
+    pub version: bool,                 // Display version flag
 }
 
 impl TryFrom<RawArgs> for Args {
@@ -45,7 +81,17 @@ impl TryFrom<RawArgs> for Args {
         let statements = query_to_statements(value.query_group.query, value.query_group.source)?;
 
         // Validate input files
-        validate_in_files(&value.in_file)?;
+        let in_file = expand_in_files(
+            &value.in_file,
+            value.in_sheet.as_deref(),
+            value.in_union.is_some(),
+        )?;
+        let in_schema = load_schema_overrides(&value.in_schema, &value.in_type)?;
+        let in_fixed_layout = match (&value.in_fixed_layout, &value.in_fixed_widths) {
+            (Some(file_path), _) => Some(FixedLayout::load(file_path)?),
+            (None, Some(widths)) => Some(FixedLayout::from_widths(widths)?),
+            (None, None) => None,
+        };
 
         // Convert single-byte arguments
         let in_comment = parse_optional_byte(&value.in_comment)?;
@@ -59,36 +105,65 @@ impl TryFrom<RawArgs> for Args {
 
         // Parse other arguments
         let in_trim = value.in_trim.into();
+        let in_range = value
+            .in_range
+            .as_deref()
+            .map(str::parse::<CellRange>)
+            .transpose()?;
         let out_quote_style = value.out_quote_style.into();
         let in_terminator = parse_terminator(&value.in_terminator)?;
         let out_terminator = parse_terminator(&value.out_terminator)?;
         validate_encoding(&value.out_encoding)?;
 
         Ok(Self {
-            in_file: value.in_file,
+            in_file,
             statements,
             help: value.help > 0,
             in_allow_leading_zeros: value.in_allow_leading_zeros > 0,
             in_comment,
+            in_date_format: value.in_date_format,
+            in_date_storage: value.in_date_storage,
             in_delimiter,
+            in_detect_boolean: value.in_detect_boolean > 0,
+            in_detect_dates: value.in_detect_dates > 0,
             in_escape,
+            in_fixed_bytes: value.in_fixed_bytes > 0,
+            in_fixed_layout,
             in_flexible: value.in_flexible > 0,
+            in_header_row: value.in_header_row,
             in_no_double_quote: value.in_no_double_quote > 0,
             in_no_header: value.in_no_header > 0,
             in_no_quoting: value.in_no_quoting > 0,
+            in_null: value.in_null,
+            in_number_locale: value.in_number_locale,
             in_quote,
+            in_range,
+            in_schema,
+            in_table_name_style: value.in_table_name_style,
             in_terminator,
             in_trim,
+            in_union: value.in_union,
+            out_all_results: value.out_all_results > 0,
+            out_blob_format: value.out_blob_format,
             out_comment,
+            out_compress: value.out_compress,
             out_database: value.out_database,
             out_delimiter,
             out_encoding: value.out_encoding,
             out_escape,
             out_file: value.out_file,
+            out_format: value.out_format,
+            out_html_class: value.out_html_class,
             out_log: value.out_log,
+            out_max_width: value.out_max_width,
             out_no_double_quote: value.out_no_double_quote > 0,
+            out_no_pager: value.out_no_pager > 0,
+            out_null: value.out_null,
             out_quote_style,
             out_quote,
+            out_sql_batch_size: value.out_sql_batch_size as usize,
+            out_sql_dialect: value.out_sql_dialect,
+            out_sql_table: value.out_sql_table,
             out_terminator,
             out_without_header: value.out_without_header > 0,
             raw_id: value.raw_id,
@@ -97,11 +172,11 @@ impl TryFrom<RawArgs> for Args {
     }
 }
 
-/// Parses SQL queries from string or file.
+/// Parses SQL queries from string or file, with their output annotations.
 fn query_to_statements(
     query: Option<String>,
     source: Option<String>,
-) -> anyhow::Result<Vec<sqlparser::ast::Statement>> {
+) -> anyhow::Result<Vec<ScriptStatement>> {
     let query_final = if let Some(query) = query {
         debug!("SQL query provided: {}", query);
         Some(query)
@@ -116,8 +191,7 @@ fn query_to_statements(
 
     if let Some(query_str) = query_final {
         debug!("Parsing SQL queries.");
-        let dialect = sqlparser::dialect::SQLiteDialect {};
-        let statements = sqlparser::parser::Parser::parse_sql(&dialect, &query_str)?;
+        let statements = parse_script(&query_str)?;
         if statements.is_empty() {
             return Err(anyhow::anyhow!("No valid SQL query provided"));
         }
@@ -127,12 +201,45 @@ fn query_to_statements(
     }
 }
 
-/// Validates existence of input files.
-fn validate_in_files(files: &[String]) -> anyhow::Result<()> {
-    for file in files {
-        std::fs::metadata(file).with_context(|| format!("File not found: {}", file))?;
+/// Loads column definitions from the schema file and inline types.
+fn load_schema_overrides(
+    in_schema: &Option<String>,
+    in_type: &[String],
+) -> anyhow::Result<SchemaOverrides> {
+    let mut overrides = match in_schema {
+        Some(file_path) => SchemaOverrides::load(file_path)?,
+        None => SchemaOverrides::default(),
+    };
+    for spec in in_type {
+        overrides.add_inline(spec)?;
+    }
+    Ok(overrides)
+}
+
+/// Expands directories, glob patterns and workbooks of input files and validates their existence.
+///
+/// # Arguments
+/// * `specs` - Values of `--in-file`.
+/// * `sheet` - Sheet imported from workbooks, given with `--in-sheet`.
+/// * `is_union` - `true` with `--in-union`, where table names given as `name=path` are not used.
+fn expand_in_files(
+    specs: &[String],
+    sheet: Option<&str>,
+    is_union: bool,
+) -> anyhow::Result<Vec<InputFile>> {
+    let mut in_file: Vec<InputFile> = vec![];
+    for spec in specs {
+        let files = InputFile::expand(spec, sheet)?;
+        if !is_union && files.len() > 1 && files[0].table_name.is_some() {
+            return Err(anyhow::anyhow!(
+                "{} matches {} inputs, but gives a single table name. Use --in-union to import them into one table.",
+                spec,
+                files.len()
+            ));
+        }
+        in_file.extend(files);
     }
-    Ok(())
+    Ok(in_file)
 }
 
 /// Parses a string into a CSV record terminator.
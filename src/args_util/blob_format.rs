@@ -0,0 +1,21 @@
+/// Text encodings for BLOB values in query results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum BlobFormat {
+    /// Lowercase hexadecimal digits.
+    #[default]
+    Hex,
+    /// Standard Base64 with padding.
+    Base64,
+}
+
+impl BlobFormat {
+    /// Encodes bytes as text in this format.
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            BlobFormat::Hex => bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            BlobFormat::Base64 => {
+                base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes)
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+/// Cells of a sheet read with `--in-range`, in A1 notation.
+///
+/// `B3:F100` reads the rectangle between both cells, and `B3` alone reads from
+/// that cell to the last used row and column.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellRange {
+    /// Zero-based row and column of the top-left cell.
+    pub start: (u32, u32),
+    /// Zero-based row and column of the bottom-right cell; `None` up to the last used cell.
+    pub end: Option<(u32, u32)>,
+}
+
+impl FromStr for CellRange {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid cell range: {} (e.g., A1:D100 or B3)", source);
+        let (start, end) = match source.split_once(':') {
+            Some((start, end)) => (
+                parse_cell(start).ok_or_else(invalid)?,
+                Some(parse_cell(end).ok_or_else(invalid)?),
+            ),
+            None => (parse_cell(source).ok_or_else(invalid)?, None),
+        };
+        if end.is_some_and(|(row, column)| row < start.0 || column < start.1) {
+            return Err(invalid());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+/// Parses a cell reference such as `B3` or `$B$3` into a zero-based row and column.
+fn parse_cell(source: &str) -> Option<(u32, u32)> {
+    let (_, letters, digits) =
+        lazy_regex::regex_captures!(r"^\$?([A-Za-z]{1,3})\$?([1-9][0-9]{0,6})$", source)?;
+    let column = letters.bytes().fold(0, |column, letter| {
+        column * 26 + u32::from(letter.to_ascii_uppercase() - b'A') + 1
+    });
+    let row: u32 = digits.parse().ok()?;
+    Some((row - 1, column - 1))
+}
+
+#[test]
+fn test_cell_range() {
+    assert_eq!(
+        "B3:AA100".parse::<CellRange>().unwrap(),
+        CellRange {
+            start: (2, 1),
+            end: Some((99, 26)),
+        }
+    );
+    assert_eq!(
+        "$a$1".parse::<CellRange>().unwrap(),
+        CellRange {
+            start: (0, 0),
+            end: None,
+        }
+    );
+    assert!("D4:A1".parse::<CellRange>().is_err());
+    assert!("A0".parse::<CellRange>().is_err());
+    assert!("1A".parse::<CellRange>().is_err());
+}
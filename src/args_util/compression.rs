@@ -0,0 +1,155 @@
+use std::io::Read;
+
+/// Compression formats of input and output files.
+///
+/// Inputs are detected from their content or extension, outputs from `--out-compress` or the extension.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum Compression {
+    /// gzip (`.gz`).
+    Gzip,
+    /// Zstandard (`.zst`).
+    Zstd,
+    /// bzip2 (`.bz2`).
+    Bzip2,
+    /// xz (`.xz`).
+    Xz,
+}
+
+impl Compression {
+    /// Detects the compression from the first bytes of the content.
+    ///
+    /// # Returns
+    /// The compression, or `None` if the content does not start with a known magic number.
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if bytes.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if bytes.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    /// Detects the compression from a file extension (case-insensitive).
+    ///
+    /// # Arguments
+    /// * `filename` - The file name or path to check.
+    ///
+    /// # Returns
+    /// The compression, or `None` if the extension is not a compressed one.
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let extension = std::path::Path::new(filename)
+            .extension()
+            .and_then(|extension| extension.to_str())?
+            .to_lowercase();
+
+        match extension.as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "bz2" => Some(Compression::Bzip2),
+            "xz" => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    /// Detects the compression from the first bytes read from the content.
+    pub fn detect<R: Read>(mut reader: R) -> std::io::Result<Option<Self>> {
+        let mut magic = [0; 6];
+        let len = read_up_to(&mut reader, &mut magic)?;
+        Ok(Self::from_magic(&magic[..len]))
+    }
+
+    /// Detects the compression of a file from its first bytes, then from its extension.
+    pub fn detect_file(file_path: &str) -> std::io::Result<Option<Self>> {
+        let compression = Self::detect(std::fs::File::open(file_path)?)?;
+        Ok(compression.or_else(|| Self::from_filename(file_path)))
+    }
+
+    /// Wraps a reader of compressed content with a streaming decoder.
+    ///
+    /// # Arguments
+    /// * `compression` - Compression of the content; `None` returns the reader as is.
+    /// * `reader` - Reader of the content as stored.
+    pub fn decoder<'r, R: Read + 'r>(
+        compression: Option<Self>,
+        reader: R,
+    ) -> std::io::Result<Box<dyn Read + 'r>> {
+        Ok(match compression {
+            None => Box::new(reader),
+            Some(Compression::Gzip) => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(reader)?),
+            Some(Compression::Bzip2) => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+            Some(Compression::Xz) => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+        })
+    }
+}
+
+/// Returns the file name without a compression extension, e.g. `events.csv` for `events.csv.gz`.
+///
+/// The inner name decides the table name and the input format.
+pub fn strip_compression_extension(filename: &str) -> &str {
+    match Compression::from_filename(filename) {
+        Some(_) => filename
+            .rsplit_once('.')
+            .map_or(filename, |(inner, _)| inner),
+        None => filename,
+    }
+}
+
+/// Reads until the buffer is full or the content ends.
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..])? {
+            0 => break,
+            read => len += read,
+        }
+    }
+    Ok(len)
+}
+
+#[test]
+fn test_detect() {
+    assert_eq!(
+        Compression::from_magic(&[0x1f, 0x8b, 0x08]),
+        Some(Compression::Gzip)
+    );
+    assert_eq!(
+        Compression::from_magic(b"BZh91AY"),
+        Some(Compression::Bzip2)
+    );
+    assert_eq!(Compression::from_magic(b"id,name"), None);
+    assert_eq!(
+        Compression::from_filename("data/events.csv.ZST"),
+        Some(Compression::Zstd)
+    );
+    assert_eq!(
+        strip_compression_extension("data/events.csv.xz"),
+        "data/events.csv"
+    );
+    assert_eq!(
+        strip_compression_extension("data/events.csv"),
+        "data/events.csv"
+    );
+}
+
+#[test]
+fn test_decoder() {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(b"id\n1\n").unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let compression = Compression::from_magic(&compressed);
+    let mut content = String::new();
+    Compression::decoder(compression, compressed.as_slice())
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "id\n1\n");
+}
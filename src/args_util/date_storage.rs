@@ -0,0 +1,9 @@
+/// Storage of imported dates and datetimes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum DateStorage {
+    /// ISO-8601 text, e.g. `2024-01-31` or `2024-01-31 13:45:00`.
+    #[default]
+    Iso,
+    /// Seconds since 1970-01-01 00:00:00 UTC.
+    Epoch,
+}
@@ -0,0 +1,138 @@
+use anyhow::Context;
+
+/// Column positions of fixed-width input, given with `--in-fixed-widths` or `--in-fixed-layout`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedLayout {
+    /// Columns in the order of the table.
+    pub columns: Vec<FixedColumn>,
+}
+
+/// A column of fixed-width input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedColumn {
+    /// Column name from the layout file; `None` takes the name from the header line.
+    pub name: Option<String>,
+    /// Zero-based position of the first character, or byte with `--in-fixed-bytes`.
+    pub start: usize,
+    /// Number of characters, or bytes.
+    pub width: usize,
+}
+
+impl FixedColumn {
+    /// Returns the position after the last character of the column.
+    pub fn end(&self) -> usize {
+        self.start + self.width
+    }
+}
+
+impl FixedLayout {
+    /// Parses consecutive column widths such as `10,5,20`.
+    pub fn from_widths(source: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid --in-fixed-widths: {} (e.g., 10,5,20)", source);
+        let mut columns: Vec<FixedColumn> = vec![];
+        let mut start: usize = 0;
+        for width in source.split(',') {
+            let width: usize = width.trim().parse().map_err(|_| invalid())?;
+            if width == 0 {
+                return Err(invalid());
+            }
+            columns.push(FixedColumn {
+                name: None,
+                start,
+                width,
+            });
+            start += width;
+        }
+        Ok(Self { columns })
+    }
+
+    /// Loads a layout file with one column per line as `name,start,width`.
+    ///
+    /// `start` counts from 1, as in record layouts of mainframe files. Columns
+    /// may leave gaps or be listed in any order. Blank lines and lines starting
+    /// with `#` are ignored.
+    ///
+    /// ```text
+    /// # name,start,width
+    /// id,1,6
+    /// name,7,20
+    /// amount,31,10
+    /// ```
+    pub fn load(file_path: &str) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read layout file {}", file_path))?;
+        Self::parse_layout(&content).with_context(|| format!("Invalid layout file {}", file_path))
+    }
+
+    /// Parses the content of a layout file.
+    fn parse_layout(content: &str) -> anyhow::Result<Self> {
+        let mut columns: Vec<FixedColumn> = vec![];
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let column = parse_layout_line(line).with_context(|| {
+                format!(
+                    "Line {}: {} (expected name,start,width, e.g., amount,31,10)",
+                    line_index + 1,
+                    line
+                )
+            })?;
+            columns.push(column);
+        }
+        if columns.is_empty() {
+            return Err(anyhow::anyhow!("No columns are defined"));
+        }
+        Ok(Self { columns })
+    }
+}
+
+/// Parses a line of a layout file into a column.
+fn parse_layout_line(line: &str) -> anyhow::Result<FixedColumn> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [name, start, width] = fields[..] else {
+        return Err(anyhow::anyhow!("Expected 3 fields"));
+    };
+    let start: usize = start.parse().context("Invalid start")?;
+    let width: usize = width.parse().context("Invalid width")?;
+    if name.is_empty() || start == 0 || width == 0 {
+        return Err(anyhow::anyhow!(
+            "The name must not be empty, and start and width must be at least 1"
+        ));
+    }
+    Ok(FixedColumn {
+        name: Some(name.to_string()),
+        start: start - 1,
+        width,
+    })
+}
+
+#[test]
+fn test_fixed_layout() {
+    let layout = FixedLayout::from_widths("3, 2,4").unwrap();
+    assert_eq!(
+        layout
+            .columns
+            .iter()
+            .map(|c| (c.start, c.end()))
+            .collect::<Vec<_>>(),
+        vec![(0, 3), (3, 5), (5, 9)]
+    );
+    assert_eq!(layout.columns[0].name, None);
+    assert!(FixedLayout::from_widths("3,0").is_err());
+    assert!(FixedLayout::from_widths("3,,2").is_err());
+
+    let layout =
+        FixedLayout::parse_layout("# name,start,width\n\nid,1,6\namount, 31, 10\n").unwrap();
+    assert_eq!(
+        layout.columns[1],
+        FixedColumn {
+            name: Some("amount".to_string()),
+            start: 30,
+            width: 10,
+        }
+    );
+    assert!(FixedLayout::parse_layout("id,0,6").is_err());
+    assert!(FixedLayout::parse_layout("id,1").is_err());
+}
@@ -0,0 +1,67 @@
+use crate::args_util::compression::strip_compression_extension;
+
+/// Extensions of workbooks read as spreadsheets.
+const SPREADSHEET_EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
+/// Input file formats, detected from the file extension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InFormat {
+    /// Delimited text (CSV, or TSV for `.tsv` files).
+    Csv,
+    /// Fixed-width text, read with `--in-fixed-widths` or `--in-fixed-layout`.
+    FixedWidth,
+    /// A JSON array of objects.
+    Json,
+    /// One JSON object per line.
+    Ndjson,
+    /// An Excel or OpenDocument workbook, one table per sheet.
+    Spreadsheet,
+    /// An Apache Parquet file.
+    Parquet,
+}
+
+impl InFormat {
+    /// Detects the input format from a file extension (case-insensitive).
+    ///
+    /// A compression extension is skipped, so `events.json.gz` is read as JSON.
+    /// Workbooks are compressed archives already and are only detected as is.
+    ///
+    /// # Arguments
+    /// * `filename` - The file name or path to check.
+    ///
+    /// # Returns
+    /// The format matching the extension. Unknown extensions are treated as `Csv`.
+    pub fn from_filename(filename: &str) -> Self {
+        if is_spreadsheet_filename(filename) {
+            return InFormat::Spreadsheet;
+        }
+        let extension = std::path::Path::new(strip_compression_extension(filename))
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+
+        if extension.eq_ignore_ascii_case("json") {
+            InFormat::Json
+        } else if extension.eq_ignore_ascii_case("ndjson")
+            || extension.eq_ignore_ascii_case("jsonl")
+        {
+            InFormat::Ndjson
+        } else if extension.eq_ignore_ascii_case("parquet") {
+            InFormat::Parquet
+        } else {
+            InFormat::Csv
+        }
+    }
+}
+
+/// Returns `true` if the file has a workbook extension such as `.xlsx` (case-insensitive).
+pub fn is_spreadsheet_filename(filename: &str) -> bool {
+    std::path::Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            SPREADSHEET_EXTENSIONS
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
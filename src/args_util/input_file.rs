@@ -0,0 +1,303 @@
+use crate::args_util::compression::strip_compression_extension;
+use crate::args_util::in_format::is_spreadsheet_filename;
+use anyhow::Context;
+
+/// File extensions imported from a directory or a ZIP archive given with `--in-file`, also when compressed.
+const LISTED_EXTENSIONS: [&str; 6] = ["csv", "tsv", "json", "ndjson", "jsonl", "parquet"];
+
+/// An input file given with `--in-file`, optionally with a table name as `name=path`.
+///
+/// A member of a ZIP archive is given as `archive.zip#member`, and a sheet of a
+/// workbook as `report.xlsx#Sheet1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputFile {
+    /// Path of the input file.
+    pub path: String,
+
+    /// Path of the member inside the archive, or name of the sheet of the workbook at `path`.
+    pub member: Option<String>,
+
+    /// Table name given on the command line.
+    pub table_name: Option<String>,
+}
+
+impl InputFile {
+    /// Parses `path`, `name=path` or `path#member`, where the member may be a sheet name.
+    ///
+    /// A value naming an existing file is always taken as a path, so files with `=`
+    /// or `#` in their name can still be imported.
+    pub fn parse(spec: &str) -> Self {
+        let (table_name, path) = if !std::path::Path::new(spec).exists()
+            && let Some((table_name, path)) = spec.split_once('=')
+            && !table_name.is_empty()
+        {
+            (Some(table_name.to_string()), path)
+        } else {
+            (None, spec)
+        };
+
+        if !std::path::Path::new(path).exists()
+            && let Some((archive, member)) = path.split_once('#')
+            && std::path::Path::new(archive).is_file()
+        {
+            return Self {
+                path: archive.to_string(),
+                member: Some(member.to_string()),
+                table_name,
+            };
+        }
+        Self {
+            path: path.to_string(),
+            member: None,
+            table_name,
+        }
+    }
+
+    /// Parses the value of `--in-file` and expands directories, glob patterns, ZIP archives and workbooks.
+    ///
+    /// A directory yields its files with a known extension, not recursively, and
+    /// a ZIP archive yields its members with a known extension. A workbook yields
+    /// its worksheets, or only `sheet` if given. Matches are sorted by path, so
+    /// tables and union rows follow a stable order.
+    ///
+    /// # Arguments
+    /// * `spec` - Value of `--in-file`.
+    /// * `sheet` - Sheet imported from workbooks, given with `--in-sheet`.
+    ///
+    /// # Returns
+    /// The matched files, or an error if nothing matches.
+    pub fn expand(spec: &str, sheet: Option<&str>) -> anyhow::Result<Vec<Self>> {
+        let input_file = Self::parse(spec);
+        if let Some(member) = &input_file.member {
+            if !list_members(&input_file.path)?.contains(member) {
+                return Err(anyhow::anyhow!(
+                    "{} not found in {}",
+                    member,
+                    input_file.path
+                ));
+            }
+            return Ok(vec![input_file]);
+        }
+
+        let path = std::path::Path::new(&input_file.path);
+        let mut paths: Vec<String> = if path.is_dir() {
+            std::fs::read_dir(path)
+                .with_context(|| format!("Failed to read directory {}", input_file.path))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .map(|path| path.to_string_lossy().to_string())
+                .filter(|path| {
+                    std::path::Path::new(path).is_file()
+                        && (has_listed_extension(path) || is_spreadsheet_filename(path))
+                })
+                .collect()
+        } else if path.exists() {
+            vec![input_file.path.clone()]
+        } else if input_file.path.contains(['*', '?', '[']) {
+            glob::glob(&input_file.path)
+                .with_context(|| format!("Invalid glob pattern: {}", input_file.path))?
+                .filter_map(|entry| entry.ok())
+                .filter(|path| path.is_file())
+                .map(|path| path.to_string_lossy().to_string())
+                .collect()
+        } else {
+            return Err(anyhow::anyhow!("File not found: {}", input_file.path));
+        };
+        paths.sort();
+
+        let mut input_files: Vec<Self> = vec![];
+        for path in paths {
+            if is_zip_filename(&path) {
+                for member in list_archive_members(&path)?
+                    .into_iter()
+                    .filter(|member| has_listed_extension(member))
+                {
+                    input_files.push(Self {
+                        path: path.clone(),
+                        member: Some(member),
+                        table_name: input_file.table_name.clone(),
+                    });
+                }
+            } else if is_spreadsheet_filename(&path) {
+                let sheets = list_sheets(&path)?;
+                if let Some(sheet) = sheet
+                    && !sheets.iter().any(|name| name == sheet)
+                {
+                    return Err(anyhow::anyhow!("Sheet {} not found in {}", sheet, path));
+                }
+                for name in sheets
+                    .into_iter()
+                    .filter(|name| sheet.is_none_or(|sheet| name == sheet))
+                {
+                    input_files.push(Self {
+                        path: path.clone(),
+                        member: Some(name),
+                        table_name: input_file.table_name.clone(),
+                    });
+                }
+            } else {
+                input_files.push(Self {
+                    path,
+                    member: None,
+                    table_name: input_file.table_name.clone(),
+                });
+            }
+        }
+
+        if input_files.is_empty() {
+            return Err(anyhow::anyhow!("No input files match {}", input_file.path));
+        }
+        Ok(input_files)
+    }
+
+    /// Returns the name of the content: the member path inside an archive, or the file path.
+    ///
+    /// The table name, the input format and the delimiter are derived from this name.
+    /// For a sheet, this is the path of the workbook.
+    pub fn inner_name(&self) -> &str {
+        match self.sheet() {
+            Some(_) => &self.path,
+            None => self.member.as_deref().unwrap_or(&self.path),
+        }
+    }
+
+    /// Returns the sheet name if the input is a sheet of a workbook.
+    pub fn sheet(&self) -> Option<&str> {
+        self.member
+            .as_deref()
+            .filter(|_| is_spreadsheet_filename(&self.path))
+    }
+}
+
+impl std::fmt::Display for InputFile {
+    /// Formats the input as given on the command line, without the table name.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.member {
+            Some(member) => write!(f, "{}#{}", self.path, member),
+            None => write!(f, "{}", self.path),
+        }
+    }
+}
+
+/// Returns `true` if the file has a `.zip` extension (case-insensitive).
+fn is_zip_filename(filename: &str) -> bool {
+    std::path::Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+/// Returns the sheet names of a workbook, or the member paths of a ZIP archive.
+fn list_members(path: &str) -> anyhow::Result<Vec<String>> {
+    if is_spreadsheet_filename(path) {
+        list_sheets(path)
+    } else {
+        list_archive_members(path)
+    }
+}
+
+/// Returns the names of the worksheets of a workbook in their order in the workbook.
+///
+/// Chart sheets and other sheets without cells are skipped.
+fn list_sheets(workbook_path: &str) -> anyhow::Result<Vec<String>> {
+    use calamine::Reader;
+
+    let workbook = calamine::open_workbook_auto(workbook_path)
+        .with_context(|| format!("Failed to open workbook {}", workbook_path))?;
+    Ok(workbook
+        .sheets_metadata()
+        .iter()
+        .filter(|sheet| sheet.typ == calamine::SheetType::WorkSheet)
+        .map(|sheet| sheet.name.clone())
+        .collect())
+}
+
+/// Returns the paths of the files in a ZIP archive, sorted.
+///
+/// Directories and macOS resource forks (`__MACOSX/`) are skipped.
+fn list_archive_members(archive_path: &str) -> anyhow::Result<Vec<String>> {
+    let file = std::fs::File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path))?;
+    let archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Failed to read ZIP archive {}", archive_path))?;
+
+    let mut members: Vec<String> = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/') && !name.starts_with("__MACOSX/"))
+        .map(|name| name.to_string())
+        .collect();
+    members.sort();
+    Ok(members)
+}
+
+/// Returns `true` if the file has an extension imported from directories and archives.
+fn has_listed_extension(path: &str) -> bool {
+    std::path::Path::new(strip_compression_extension(path))
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            LISTED_EXTENSIONS
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        InputFile::parse("sales24=data/2024/sales.csv"),
+        InputFile {
+            path: "data/2024/sales.csv".to_string(),
+            member: None,
+            table_name: Some("sales24".to_string()),
+        }
+    );
+    assert_eq!(InputFile::parse("data/sales.csv").table_name, None);
+    assert_eq!(InputFile::parse("=data.csv").path, "=data.csv");
+}
+
+#[test]
+fn test_expand() {
+    use std::io::Write;
+
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["b.csv", "a.tsv", "c.json.gz", "notes.txt"] {
+        std::fs::write(dir.path().join(name), "x\n1\n").unwrap();
+    }
+    let mut zip_writer =
+        zip::ZipWriter::new(std::fs::File::create(dir.path().join("bundle.zip")).unwrap());
+    for name in ["inner/y.csv", "readme.txt", "x.tsv"] {
+        zip_writer
+            .start_file(name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip_writer.write_all(b"x\n1\n").unwrap();
+    }
+    zip_writer.finish().unwrap();
+    let dir_path = dir.path().to_string_lossy().to_string();
+
+    let names = |spec: &str| -> Vec<String> {
+        let files = InputFile::expand(spec, None).unwrap();
+        files
+            .iter()
+            .map(|file| {
+                file.inner_name()
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    };
+    assert_eq!(names(&dir_path), vec!["a.tsv", "b.csv", "c.json.gz"]);
+    assert_eq!(names(&format!("{}/*.txt", dir_path)), vec!["notes.txt"]);
+    assert_eq!(
+        InputFile::expand(&format!("t={}/b.*", dir_path), None).unwrap()[0].table_name,
+        Some("t".to_string())
+    );
+    assert!(InputFile::expand(&format!("{}/*.json", dir_path), None).is_err());
+
+    let bundle = format!("{}/bundle.zip", dir_path);
+    assert_eq!(names(&bundle), vec!["y.csv", "x.tsv"]);
+    let files = InputFile::expand(&format!("{}#x.tsv", bundle), None).unwrap();
+    assert_eq!(files[0].to_string(), format!("{}#x.tsv", bundle));
+    assert!(InputFile::expand(&format!("{}#z.csv", bundle), None).is_err());
+}
@@ -1,4 +1,6 @@
-/// Checks if a filename has a `.tsv` extension (case-insensitive).
+use crate::args_util::compression::strip_compression_extension;
+
+/// Checks if a filename has a `.tsv` extension (case-insensitive), also before a compression extension.
 ///
 /// # Arguments
 /// * `filename` - The file name or path to check.
@@ -7,7 +9,7 @@
 /// `true` if the file has a `.tsv` extension, `false` otherwise.
 pub fn is_tsv_filename(filename: &str) -> bool {
     // Convert to Path object
-    let path = std::path::Path::new(filename);
+    let path = std::path::Path::new(strip_compression_extension(filename));
 
     // Check for extension
     if let Some(extension) = path.extension() {
@@ -0,0 +1,94 @@
+/// Number conventions for input values with grouping separators.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum NumberLocale {
+    /// `1,234,567.89`
+    En,
+    /// `1.234.567,89`
+    De,
+    /// `1 234 567,89` (space, no-break space or narrow no-break space)
+    Fr,
+}
+
+impl NumberLocale {
+    /// Returns the decimal mark.
+    fn decimal_mark(&self) -> char {
+        match self {
+            NumberLocale::En => '.',
+            NumberLocale::De | NumberLocale::Fr => ',',
+        }
+    }
+
+    /// Returns the characters accepted as grouping separators.
+    fn group_separators(&self) -> &'static [char] {
+        match self {
+            NumberLocale::En => &[','],
+            NumberLocale::De => &['.'],
+            NumberLocale::Fr => &[' ', '\u{a0}', '\u{202f}'],
+        }
+    }
+
+    /// Rewrites a number in this locale with `.` as decimal mark and no grouping separators.
+    ///
+    /// Groups must have three digits, except the first one with one to three digits.
+    ///
+    /// # Returns
+    /// `None` if the grouping is malformed. The result is not validated as a number otherwise.
+    pub fn normalize(&self, source: &str) -> Option<String> {
+        let (sign, body) = match source.strip_prefix('-') {
+            Some(body) => ("-", body),
+            None => ("", source),
+        };
+        let (mantissa, exponent) = body.split_at(body.find(['e', 'E']).unwrap_or(body.len()));
+        let (integer, fraction) = match mantissa.split_once(self.decimal_mark()) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (mantissa, None),
+        };
+
+        let separators = self.group_separators();
+        let integer = if integer.contains(separators) {
+            let groups: Vec<&str> = integer.split(separators).collect();
+            let first_valid = (1..=3).contains(&groups[0].len());
+            if !first_valid || groups[1..].iter().any(|group| group.len() != 3) {
+                return None;
+            }
+            groups.concat()
+        } else {
+            integer.to_string()
+        };
+
+        let mut normalized = format!("{}{}", sign, integer);
+        if let Some(fraction) = fraction {
+            normalized.push('.');
+            normalized.push_str(fraction);
+        }
+        normalized.push_str(exponent);
+        Some(normalized)
+    }
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(
+        NumberLocale::En.normalize("1,234,567").as_deref(),
+        Some("1234567")
+    );
+    assert_eq!(
+        NumberLocale::En.normalize("-1,234.5e3").as_deref(),
+        Some("-1234.5e3")
+    );
+    assert_eq!(
+        NumberLocale::En.normalize("1234.5").as_deref(),
+        Some("1234.5")
+    );
+    assert_eq!(
+        NumberLocale::De.normalize("1.234,56").as_deref(),
+        Some("1234.56")
+    );
+    assert_eq!(
+        NumberLocale::Fr.normalize("1\u{a0}234,5").as_deref(),
+        Some("1234.5")
+    );
+    assert_eq!(NumberLocale::En.normalize("12,34"), None);
+    assert_eq!(NumberLocale::De.normalize("1.5"), None);
+    assert_eq!(NumberLocale::En.normalize(",234"), None);
+}
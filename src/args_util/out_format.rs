@@ -0,0 +1,79 @@
+use crate::args_util::compression::strip_compression_extension;
+
+/// Output formats for query results.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum OutFormat {
+    /// Delimited text (CSV, or TSV for `.tsv` files).
+    Csv,
+    /// A JSON array of objects.
+    Json,
+    /// One JSON object per line.
+    Ndjson,
+    /// An Excel workbook with typed cells.
+    Xlsx,
+    /// An Apache Parquet file with typed columns.
+    Parquet,
+    /// Aligned columns for reading in a terminal.
+    Table,
+    /// A Markdown table.
+    Markdown,
+    /// Aligned columns framed with box-drawing characters.
+    Box,
+    /// An HTML table.
+    Html,
+    /// A LaTeX `tabular` environment.
+    Latex,
+    /// `CREATE TABLE` and `INSERT` statements.
+    Sql,
+}
+
+impl OutFormat {
+    /// Detects the output format from a file extension (case-insensitive).
+    ///
+    /// A compression extension is skipped, so `out.json.gz` is written as JSON.
+    ///
+    /// # Arguments
+    /// * `filename` - The file name or path to check.
+    ///
+    /// # Returns
+    /// The format matching the extension, or `None` if it is not recognized.
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let extension = std::path::Path::new(strip_compression_extension(filename))
+            .extension()?
+            .to_str()?;
+
+        if extension.eq_ignore_ascii_case("csv") || extension.eq_ignore_ascii_case("tsv") {
+            Some(OutFormat::Csv)
+        } else if extension.eq_ignore_ascii_case("json") {
+            Some(OutFormat::Json)
+        } else if extension.eq_ignore_ascii_case("ndjson")
+            || extension.eq_ignore_ascii_case("jsonl")
+        {
+            Some(OutFormat::Ndjson)
+        } else if extension.eq_ignore_ascii_case("xlsx") {
+            Some(OutFormat::Xlsx)
+        } else if extension.eq_ignore_ascii_case("parquet") {
+            Some(OutFormat::Parquet)
+        } else if extension.eq_ignore_ascii_case("md") {
+            Some(OutFormat::Markdown)
+        } else if extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm") {
+            Some(OutFormat::Html)
+        } else if extension.eq_ignore_ascii_case("tex") {
+            Some(OutFormat::Latex)
+        } else if extension.eq_ignore_ascii_case("sql") {
+            Some(OutFormat::Sql)
+        } else {
+            None
+        }
+    }
+
+    /// Resolves the output format.
+    ///
+    /// An explicit format takes precedence, then the extension of the output file.
+    /// Falls back to `Csv`.
+    pub fn resolve(out_format: Option<OutFormat>, out_file: &Option<String>) -> Self {
+        out_format
+            .or_else(|| out_file.as_deref().and_then(Self::from_filename))
+            .unwrap_or(OutFormat::Csv)
+    }
+}
@@ -1,11 +1,18 @@
+use crate::args_util::blob_format::BlobFormat;
+use crate::args_util::compression::Compression;
+use crate::args_util::date_storage::DateStorage;
+use crate::args_util::number_locale::NumberLocale;
+use crate::args_util::out_format::OutFormat;
 use crate::args_util::quote_style::MyQuoteStyle;
+use crate::args_util::sql_dialect::SqlDialect;
+use crate::args_util::table_name_style::TableNameStyle;
 use crate::args_util::trim::MyTrim;
 
 /// Represents raw command-line arguments parsed by `clap`.
 #[derive(clap::Parser, Debug)]
 #[command(version, about, disable_help_flag = true, disable_version_flag = true)]
 pub struct RawArgs {
-    /// Input files to process. `.tsv` files use tab as the delimiter.
+    /// Input files, directories or glob patterns (e.g., `'logs/*.csv'`) to process, optionally as `name=path` to set the table name. `.tsv` files use tab as the delimiter; `.json`, `.ndjson` and `.jsonl` files are read as JSON, each sheet of `.xlsx`, `.xls` and `.ods` workbooks becomes a table, and `.parquet` files are read as Parquet.
     #[clap(short, long)]
     pub in_file: Vec<String>,
 
@@ -25,18 +32,50 @@ pub struct RawArgs {
     #[clap(long)]
     pub in_comment: Option<String>,
 
+    /// Date format for input values, in `strftime` syntax (e.g., `%m/%d/%Y`). Replaces the built-in formats. Can be repeated.
+    #[clap(long)]
+    pub in_date_format: Vec<String>,
+
+    /// Storage of dates and datetimes detected in input values.
+    #[arg(long, value_enum, default_value = "iso")]
+    pub in_date_storage: DateStorage,
+
     /// Delimiter for input CSV (single byte, e.g., `,` or `\t`).
     #[clap(long, default_value = ",")]
     pub in_delimiter: String,
 
+    /// Import `true`/`false`, `yes`/`no`, `Y`/`N` and `0`/`1` columns as BOOLEAN rather than numbers or text.
+    #[clap(long, action = clap::ArgAction::Count)]
+    pub in_detect_boolean: u8,
+
+    /// Import dates, datetimes and times as DATE, DATETIME and TIME columns, rewritten to ISO 8601 (e.g., `31.01.2024` as `2024-01-31`).
+    #[clap(long, action = clap::ArgAction::Count)]
+    pub in_detect_dates: u8,
+
     /// Escape character for parsing CSV (single byte).
     #[clap(long)]
     pub in_escape: Option<String>,
 
+    /// Count the positions of `--in-fixed-widths` and `--in-fixed-layout` in bytes of the encoded line rather than characters.
+    #[clap(long, action = clap::ArgAction::Count)]
+    pub in_fixed_bytes: u8,
+
+    /// Layout file of fixed-width input with one `name,start,width` line per column; `start` counts from 1.
+    #[clap(long, conflicts_with = "in_fixed_widths")]
+    pub in_fixed_layout: Option<String>,
+
+    /// Column widths of fixed-width input (e.g., `10,5,20`). Column names are read from the header line.
+    #[clap(long)]
+    pub in_fixed_widths: Option<String>,
+
     /// Allow CSV rows with varying field counts.
     #[clap(long, action = clap::ArgAction::Count)]
     pub in_flexible: u8,
 
+    /// Row number of the header in spreadsheet inputs, starting at 1. Rows above it are skipped.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub in_header_row: Option<u32>,
+
     /// Disable escaping of double quotes in CSV fields.
     #[clap(long, action = clap::ArgAction::Count)]
     pub in_no_double_quote: u8,
@@ -49,10 +88,35 @@ pub struct RawArgs {
     #[clap(long, action = clap::ArgAction::Count)]
     pub in_no_quoting: u8,
 
+    /// Value treated as SQL NULL in input CSV and JSON strings, in addition to empty fields (e.g., `NA`, `\N`). Can be repeated.
+    #[clap(long)]
+    pub in_null: Vec<String>,
+
+    /// Number format with grouping separators and decimal mark for input values (e.g., `de` for `1.234,56`).
+    #[arg(long, value_enum)]
+    pub in_number_locale: Option<NumberLocale>,
+
     /// Quote character for parsing CSV (single byte).
     #[clap(long, default_value = "\"")]
     pub in_quote: String,
 
+    /// Cells read from spreadsheet inputs, in A1 notation (e.g., `B3:F100`, or `B3` up to the last used cell).
+    #[clap(long)]
+    pub in_range: Option<String>,
+
+    /// Schema file (TOML or JSON) giving column types, NOT NULL, DEFAULT and names per table.
+    #[clap(long)]
+    pub in_schema: Option<String>,
+
+    /// Sheet imported from spreadsheet inputs, rather than all sheets as `report.xlsx#Sheet1`.
+    #[clap(long)]
+    pub in_sheet: Option<String>,
+
+    /// How table names are derived from input file paths and ZIP member paths. The default `basename` drops the folders, so use `path` for members such as `a/data.csv` and `b/data.csv`.
+    #[clap(long, default_value = "basename")]
+    #[arg(value_enum)]
+    pub in_table_name_style: TableNameStyle,
+
     /// Record terminator for parsing CSV (single byte, e.g, `\n`). Use `CRLF` for `\r\n`.
     #[clap(long, default_value = "CRLF")]
     pub in_terminator: String,
@@ -62,6 +126,23 @@ pub struct RawArgs {
     #[arg(value_enum)]
     pub in_trim: MyTrim,
 
+    /// Import all input files into this table, matching columns by name and adding a `_source_file` column.
+    #[clap(long)]
+    pub in_union: Option<String>,
+
+    /// Column types for a table, replacing inference (e.g., `file.csv:zip=TEXT,amount=REAL`). Can be repeated.
+    #[clap(long)]
+    pub in_type: Vec<String>,
+
+    /// Write the result of every SELECT and PRAGMA statement, rather than only the last one.
+    #[clap(long, action = clap::ArgAction::Count)]
+    pub out_all_results: u8,
+
+    /// Text encoding for BLOB values in query results.
+    #[clap(long, default_value = "hex")]
+    #[arg(value_enum)]
+    pub out_blob_format: BlobFormat,
+
     /// Comment character for output CSV (single byte).
     #[clap(long)]
     pub out_comment: Option<String>,
@@ -70,6 +151,11 @@ pub struct RawArgs {
     #[clap(long)]
     pub out_database: Option<String>,
 
+    /// Compression of the output. Detected from a `.gz`, `.zst`, `.bz2` or `.xz` `--out-file` extension if omitted.
+    #[clap(long)]
+    #[arg(value_enum)]
+    pub out_compress: Option<Compression>,
+
     /// Delimiter for output CSV (single byte, e.g., `,` or `\t`).
     #[clap(long, default_value = ",")]
     pub out_delimiter: String,
@@ -82,18 +168,39 @@ pub struct RawArgs {
     #[clap(long)]
     pub out_escape: Option<String>,
 
-    /// Output file path. `.tsv` files use tab as the delimiter.
+    /// Output file path. `.tsv` files use tab as the delimiter; `.json`, `.ndjson` and `.jsonl` files are written as JSON, `.xlsx` files as an Excel workbook, `.parquet` files as Parquet, `.html` files as an HTML table, `.tex` files as a LaTeX table, and `.sql` files as SQL statements.
     #[clap(short, long)]
     pub out_file: Option<String>,
 
+    /// Output format for query results. Detected from the `--out-file` extension if omitted.
+    #[clap(long)]
+    #[arg(value_enum)]
+    pub out_format: Option<OutFormat>,
+
+    /// Class attribute of the table in `html` output, for styling it with CSS.
+    #[clap(long)]
+    pub out_html_class: Option<String>,
+
     /// Path to the log file.
     #[clap(long)]
     pub out_log: Option<String>,
 
+    /// Display width at which values are truncated with `…` in `table`, `markdown` and `box` output. `0` keeps values whole.
+    #[clap(long, default_value = "40")]
+    pub out_max_width: usize,
+
     /// Disable escaping of double quotes in output CSV.
     #[clap(long, action = clap::ArgAction::Count)]
     pub out_no_double_quote: u8,
 
+    /// Print `table`, `markdown` and `box` output directly, rather than through a pager when it exceeds the terminal height.
+    #[clap(long, action = clap::ArgAction::Count)]
+    pub out_no_pager: u8,
+
+    /// Text written for SQL NULL in output CSV.
+    #[clap(long, default_value = "")]
+    pub out_null: String,
+
     /// Quoting style for output CSV.
     #[clap(long, default_value = "necessary")]
     #[arg(value_enum)]
@@ -103,6 +210,19 @@ pub struct RawArgs {
     #[clap(long, default_value = "\"")]
     pub out_quote: String,
 
+    /// Number of rows per `INSERT` statement in `sql` output.
+    #[clap(long, default_value = "500", value_parser = clap::value_parser!(u64).range(1..))]
+    pub out_sql_batch_size: u64,
+
+    /// SQL dialect of `sql` output.
+    #[clap(long, default_value = "sqlite")]
+    #[arg(value_enum)]
+    pub out_sql_dialect: SqlDialect,
+
+    /// Table name in `sql` output. Defaults to the `--out-file` name without extensions, or `result`.
+    #[clap(long)]
+    pub out_sql_table: Option<String>,
+
     /// Record terminator for output CSV (single byte, e.g, `\n`). Use `CRLF` for `\r\n`.
     #[clap(long, default_value = "CRLF")]
     pub out_terminator: String,
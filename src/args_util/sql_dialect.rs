@@ -0,0 +1,11 @@
+/// SQL dialects of `sql` output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum SqlDialect {
+    /// SQLite, keeping declared types and stored values as they are.
+    #[default]
+    Sqlite,
+    /// PostgreSQL.
+    Postgres,
+    /// MySQL and MariaDB.
+    Mysql,
+}
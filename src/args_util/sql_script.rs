@@ -0,0 +1,175 @@
+/// A statement of an SQL script, with the file its result is routed to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptStatement {
+    pub statement: sqlparser::ast::Statement,
+    /// Output file given by an annotation; `None` for the default output.
+    pub output: Option<String>,
+}
+
+impl From<sqlparser::ast::Statement> for ScriptStatement {
+    /// Wraps a statement without routing, so its result goes to the default output.
+    fn from(statement: sqlparser::ast::Statement) -> Self {
+        Self {
+            statement,
+            output: None,
+        }
+    }
+}
+
+/// Output annotation on a line of its own.
+#[derive(Debug, PartialEq)]
+enum Directive {
+    /// `-- @output <file>`: routes the result of the next statement.
+    Once(String),
+    /// `.output <file>`: routes the results of all following statements, or
+    /// with no file, returns them to the default output.
+    Output(Option<String>),
+}
+
+/// Parses an SQL script with output annotations.
+///
+/// ```sql
+/// CREATE TABLE summary AS SELECT ...;
+/// -- @output summary.tsv
+/// SELECT * FROM summary;
+/// .output details.json
+/// SELECT ...;
+/// ```
+///
+/// Annotations must be on lines of their own, between statements.
+pub fn parse_script(script: &str) -> anyhow::Result<Vec<ScriptStatement>> {
+    let mut statements: Vec<ScriptStatement> = vec![];
+    // SQL since the last annotation, and the line it starts at
+    let mut sql = String::new();
+    let mut sql_line: usize = 1;
+    let mut once: Option<(String, usize)> = None;
+    let mut output: Option<String> = None;
+
+    for (line_index, line) in script.lines().enumerate() {
+        let Some(directive) = parse_directive(line)
+            .map_err(|err| anyhow::anyhow!("{} at line {}", err, line_index + 1))?
+        else {
+            sql.push_str(line);
+            sql.push('\n');
+            continue;
+        };
+        parse_segment(&sql, sql_line, &mut once, &output, &mut statements)?;
+        match directive {
+            Directive::Once(file) => once = Some((file, line_index + 1)),
+            Directive::Output(file) => output = file,
+        }
+        sql.clear();
+        sql_line = line_index + 2;
+    }
+    parse_segment(&sql, sql_line, &mut once, &output, &mut statements)?;
+
+    if let Some((file, line_number)) = once {
+        return Err(anyhow::anyhow!(
+            "No statement follows \"-- @output {}\" at line {}",
+            file,
+            line_number
+        ));
+    }
+    Ok(statements)
+}
+
+/// Parses the SQL between two annotations and routes its statements.
+fn parse_segment(
+    sql: &str,
+    sql_line: usize,
+    once: &mut Option<(String, usize)>,
+    output: &Option<String>,
+    statements: &mut Vec<ScriptStatement>,
+) -> anyhow::Result<()> {
+    let dialect = sqlparser::dialect::SQLiteDialect {};
+    let parsed = sqlparser::parser::Parser::parse_sql(&dialect, sql)
+        .map_err(|err| anyhow::anyhow!("Invalid SQL starting at line {}: {}", sql_line, err))?;
+    for statement in parsed {
+        let statement_output = match once.take() {
+            Some((file, _)) => Some(file),
+            None => output.clone(),
+        };
+        statements.push(ScriptStatement {
+            statement,
+            output: statement_output,
+        });
+    }
+    Ok(())
+}
+
+/// Reads an output annotation from a line.
+///
+/// # Returns
+/// The annotation, or `None` for a line of SQL.
+fn parse_directive(line: &str) -> anyhow::Result<Option<Directive>> {
+    let line = line.trim();
+    if let Some(file) = line
+        .strip_prefix("--")
+        .and_then(|comment| comment.trim_start().strip_prefix("@output"))
+        .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    {
+        return match file_name(file) {
+            Some(file) => Ok(Some(Directive::Once(file))),
+            None => Err(anyhow::anyhow!("-- @output needs a file name")),
+        };
+    }
+    if let Some(file) = line
+        .strip_prefix(".output")
+        .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    {
+        return Ok(Some(Directive::Output(file_name(file))));
+    }
+    Ok(None)
+}
+
+/// Returns the file name of an annotation, without surrounding quotes.
+fn file_name(source: &str) -> Option<String> {
+    let source = source.trim().trim_end_matches(';').trim_end();
+    let unquoted = ['"', '\'']
+        .iter()
+        .find_map(|quote| {
+            source
+                .strip_prefix(*quote)
+                .and_then(|rest| rest.strip_suffix(*quote))
+        })
+        .unwrap_or(source);
+    Some(unquoted.to_string()).filter(|file| !file.is_empty())
+}
+
+#[test]
+fn test_parse_script() {
+    let statements = parse_script(
+        "CREATE TABLE t (a);\n\
+         -- @output 'first file.tsv'\n\
+         SELECT 1; SELECT 2;\n\
+         .output out.json\n\
+         -- a comment\n\
+         SELECT 3;\n\
+         -- @output once.csv\n\
+         SELECT 4;\n\
+         SELECT 5;\n\
+         .output\n\
+         SELECT 6;\n",
+    )
+    .unwrap();
+    let outputs: Vec<Option<&str>> = statements
+        .iter()
+        .map(|statement| statement.output.as_deref())
+        .collect();
+    assert_eq!(
+        outputs,
+        vec![
+            None,
+            Some("first file.tsv"),
+            None,
+            Some("out.json"),
+            Some("once.csv"),
+            Some("out.json"),
+            None
+        ]
+    );
+
+    assert!(parse_script("SELECT 1;\n-- @output a.csv\n").is_err());
+    assert!(parse_script("-- @output\nSELECT 1;").is_err());
+    assert!(parse_script("SELECT\n-- @output a.csv\n1;").is_err());
+}
@@ -0,0 +1,11 @@
+/// How table names are derived from input file paths.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum TableNameStyle {
+    /// File name without its extension, e.g. `sales`.
+    Stem,
+    /// File name, e.g. `sales.csv`, also for ZIP members in folders.
+    #[default]
+    Basename,
+    /// Path as given or inside a ZIP archive, e.g. `data/2024/sales.csv`.
+    Path,
+}
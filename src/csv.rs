@@ -1,2 +1,3 @@
 pub mod csv_parser;
-pub mod csv_writer;
\ No newline at end of file
+pub mod csv_writer;
+pub mod fixed_width_parser;
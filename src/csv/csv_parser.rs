@@ -1,15 +1,18 @@
 use crate::args_util::args::Args;
-use crate::db::sqlite_data_type::SqliteDataType;
-use crate::db::sqlite_quoted::SqliteQuoted;
+use crate::args_util::input_file::InputFile;
+use crate::db::detect_options::DetectOptions;
+use crate::db::table_loader::TableLoader;
+use crate::db::table_schema::TableSchema;
 use crate::format::STYLE_BAR;
+use crate::input::input_stream::{
+    detect_input_encoding, input_size, spool_stdin, with_input_stream,
+};
 use anyhow::Context;
-use std::collections::HashMap;
-use std::io::{IsTerminal, Read};
+use std::io::Read;
 
-use crate::db::is_reserved_table_name::is_reserved_table_name;
 use tracing::debug;
 
-/// Parses CSV data and generates SQL statements
+/// Parses CSV data and imports it into the SQLite database
 #[derive(Debug)]
 pub struct CsvParser<'a> {
     /// Reference to application arguments
@@ -23,267 +26,326 @@ impl<'a> CsvParser<'a> {
         Self { args }
     }
 
-    /// Parses CSV from standard input and generates SQL statements
+    /// Parses CSV from standard input and imports it as the `stdin` table
+    ///
+    /// STDIN is spooled to a temporary file so that it can be read twice
+    /// without keeping its content in memory.
+    ///
+    /// # Returns
+    /// The table name, or `None` if STDIN is interactive.
     pub fn parse_stdin(
         &self,
+        conn: &rusqlite::Connection,
         encoding: Option<String>,
         delimiter: u8,
-    ) -> anyhow::Result<(String, Vec<String>)> {
+    ) -> anyhow::Result<Option<String>> {
         debug!(
             "Parsing stdin with encoding: {:?}, delimiter: {}",
             encoding, delimiter
         );
 
-        let mut stdin_content = Vec::new();
-        {
-            let mut stdin = std::io::stdin().lock();
-            if stdin.is_terminal() {
-                // If stdin is interactive, return an empty result
-                return Ok(("".to_string(), Vec::new()));
-            }
-            stdin
-                .read_to_end(&mut stdin_content)
-                .context("Failed to read from stdin")?;
-        }
-
-        let encoding_detected = encoding.unwrap_or_else(|| {
-            self.detect_encoding(&mut std::io::BufReader::new(stdin_content.as_slice()))
-                .unwrap()
-        });
-        debug!("Detected encoding: {}", encoding_detected);
-
-        let buf_reader = std::io::BufReader::new(stdin_content.as_slice());
-        let csv_reader = self.get_csv_reader(buf_reader, encoding_detected, delimiter)?;
+        let Some(spool) = spool_stdin()? else {
+            return Ok(None);
+        };
 
-        self.parse(csv_reader, "stdin", None)
+        // Each pass reads the spooled content from the beginning
+        let input_file = InputFile {
+            path: spool.path().to_string_lossy().to_string(),
+            member: None,
+            table_name: None,
+        };
+        self.parse_file(conn, &input_file, "stdin", encoding, delimiter)?;
+        Ok(Some("stdin".to_string()))
     }
 
-    /// Parses CSV from a file and generates SQL statements
+    /// Parses CSV from a file and imports it as the given table
+    ///
+    /// Compressed files and archive members are read on the fly.
     pub fn parse_file(
         &self,
-        file_path: &str,
+        conn: &rusqlite::Connection,
+        input_file: &InputFile,
+        table_name: &str,
         encoding: Option<String>,
         delimiter: u8,
-    ) -> anyhow::Result<(String, Vec<String>)> {
+    ) -> anyhow::Result<()> {
         debug!(
             "Parsing file: {} with encoding: {:?}, delimiter: {}",
-            file_path, encoding, delimiter
+            input_file, encoding, delimiter
         );
 
-        let check_path = std::path::Path::new(file_path);
-        let check_file_name = check_path.file_name().unwrap().to_str().unwrap();
-        if is_reserved_table_name(check_file_name) {
-            return Err(anyhow::anyhow!(
-                "You cannot use the SQLite's reserved word 'sqlite_' as the name of the input file: {}",
-                check_file_name
-            ));
+        let encoding_detected = match encoding {
+            Some(encoding) => encoding,
+            None => detect_input_encoding(input_file)?,
         };
 
-        let encoding_detected = encoding.unwrap_or_else(|| {
-            self.detect_encoding(&mut std::io::BufReader::new(
-                std::fs::File::open(file_path).unwrap(),
-            ))
-            .unwrap()
-        });
-
         debug!(
             "Detected encoding: {} for file: {}",
-            encoding_detected, file_path
+            encoding_detected, input_file
         );
 
-        // Create a buffered reader for the file content
-        let file = std::fs::File::open(file_path)?;
-        let buf_reader = std::io::BufReader::new(file);
-        let csv_reader = self.get_csv_reader(buf_reader, encoding_detected, delimiter)?;
-
-        let path = std::path::Path::new(file_path);
-        let file_name = path
-            .file_name()
-            .context(format!("{} not found", file_path))?
-            .to_str()
-            .context("Cannot convert OsStr to Str")?;
-        let buf_size = path.metadata()?.len();
-
-        // Parse the CSV content into SQL statements
-        self.parse(csv_reader, file_name, Some(buf_size))
+        // Scan and import the CSV content
+        self.parse(conn, input_file, &encoding_detected, delimiter, table_name)
     }
 
-    /// Processes the CSV reader and generates SQL statements
-    fn parse<T: Read>(
+    /// Scans a file into a schema shared by several inputs
+    ///
+    /// Columns are matched by name, so files with different column orders or
+    /// missing columns can be combined.
+    pub fn scan_file(
         &self,
-        mut csv_reader: CsvReaderType<T>,
+        input_file: &InputFile,
+        delimiter: u8,
+        schema: &mut TableSchema,
+    ) -> anyhow::Result<()> {
+        let encoding = detect_input_encoding(input_file)?;
+        let pb = Self::progress_bar(Some(input_size(input_file)?));
+        pb.set_message(format!("Scanning CSV: {}", input_file));
+
+        with_input_stream(input_file, &pb, |reader| {
+            let csv_reader =
+                self.get_csv_reader(std::io::BufReader::new(reader), &encoding, delimiter)?;
+            self.scan(csv_reader, &input_file.to_string(), schema)
+        })?;
+        pb.finish_and_clear();
+        Ok(())
+    }
+
+    /// Inserts the records of a file scanned by `scan_file`
+    ///
+    /// # Arguments
+    /// * `source` - Column index and value filled in for every record, e.g. the file name.
+    ///
+    /// # Returns
+    /// The number of rows inserted.
+    pub fn load_file(
+        &self,
+        input_file: &InputFile,
+        delimiter: u8,
+        schema: &TableSchema,
+        loader: &TableLoader,
+        source: Option<(usize, &str)>,
+    ) -> anyhow::Result<usize> {
+        let encoding = detect_input_encoding(input_file)?;
+        let pb = Self::progress_bar(Some(input_size(input_file)?));
+        pb.set_message(format!("Importing data from {}", input_file));
+
+        let row_count = with_input_stream(input_file, &pb, |reader| {
+            let csv_reader =
+                self.get_csv_reader(std::io::BufReader::new(reader), &encoding, delimiter)?;
+            self.load(csv_reader, schema, loader, source)
+        })?;
+        pb.finish_and_clear();
+        Ok(row_count)
+    }
+
+    /// Imports CSV content into a new table in two passes
+    ///
+    /// The first pass infers column types and nullability, the second pass
+    /// creates the table and streams rows into it. Only the column layout is
+    /// kept in memory, regardless of the input size.
+    ///
+    /// # Arguments
+    /// * `conn` - Connection to import into.
+    /// * `input_file` - The input; read once per pass.
+    /// * `encoding` - Character encoding of the content.
+    /// * `delimiter` - Field delimiter.
+    /// * `buf_name` - Table name.
+    fn parse(
+        &self,
+        conn: &rusqlite::Connection,
+        input_file: &InputFile,
+        encoding: &str,
+        delimiter: u8,
         buf_name: &str,
-        buf_size: Option<u64>,
-    ) -> anyhow::Result<(String, Vec<String>)> {
-        debug!("Parsing table: {}, size: {:?}", buf_name, buf_size);
+    ) -> anyhow::Result<()> {
+        let buf_size = input_size(input_file)?;
+        debug!("Parsing table: {}, size: {}", buf_name, buf_size);
+        let pb = Self::progress_bar(Some(buf_size));
+
+        // First pass: infer the column layout
+        pb.set_message(format!("Scanning CSV: {}", buf_name));
+        let mut schema = TableSchema::new(
+            DetectOptions::from(self.args),
+            self.args.in_schema.table(buf_name),
+        );
+        with_input_stream(input_file, &pb, |reader| {
+            let csv_reader =
+                self.get_csv_reader(std::io::BufReader::new(reader), encoding, delimiter)?;
+            self.scan(csv_reader, buf_name, &mut schema)
+        })?;
+        schema.finish(buf_name)?;
+
+        // Second pass: create the table and insert rows
+        pb.set_message(format!("Importing data from {}", buf_name));
+        pb.set_position(0);
+        let loader = TableLoader::new(conn, buf_name, &self.args.raw_id, &schema)?;
+        let row_count = with_input_stream(input_file, &pb, |reader| {
+            let csv_reader =
+                self.get_csv_reader(std::io::BufReader::new(reader), encoding, delimiter)?;
+            self.load(csv_reader, &schema, &loader, None)
+        })?;
+        loader.finish()?;
+
+        pb.finish_and_clear();
+        debug!(
+            "Finished parsing and loading data into table: {}, rows: {}",
+            buf_name, row_count
+        );
+        Ok(())
+    }
 
+    /// Creates a progress bar for content of the given size in bytes
+    fn progress_bar(buf_size: Option<u64>) -> indicatif::ProgressBar {
         let pb = if let Some(buf_size) = buf_size {
             indicatif::ProgressBar::new(buf_size)
         } else {
             indicatif::ProgressBar::no_length()
         };
         pb.set_style(STYLE_BAR.clone());
-        pb.set_message("Parsing CSV");
+        pb
+    }
 
-        let mut headers = if self.args.in_no_header {
-            vec![]
+    /// Returns the column names of the header row, or `None` without a header row
+    fn header_names<T: Read>(
+        &self,
+        csv_reader: &mut CsvReaderType<T>,
+    ) -> anyhow::Result<Option<Vec<String>>> {
+        if self.args.in_no_header {
+            Ok(None)
         } else {
-            csv_reader
-                .headers()?
-                .iter()
-                .map(|v| v.to_string())
-                .collect()
-        };
+            Ok(Some(
+                csv_reader
+                    .headers()?
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect(),
+            ))
+        }
+    }
 
-        let mut temp_types: HashMap<usize, SqliteDataType> = HashMap::new();
-        let mut temp_nullable: HashMap<usize, bool> = HashMap::new();
-        let mut temp_records: Vec<Vec<Option<String>>> = vec![];
+    /// Returns the column names of a record: the header row, or `c1`, `c2`, ... without one
+    fn record_headers(
+        header_names: &Option<Vec<String>>,
+        record: &csv::StringRecord,
+    ) -> Vec<String> {
+        match header_names {
+            Some(header_names) => header_names.clone(),
+            None => (1..=record.len())
+                .map(|index| format!("c{}", index))
+                .collect(),
+        }
+    }
+
+    /// Scans all records and infers the column types and nullability
+    fn scan<T: Read>(
+        &self,
+        mut csv_reader: CsvReaderType<T>,
+        buf_name: &str,
+        schema: &mut TableSchema,
+    ) -> anyhow::Result<()> {
+        let header_names = self.header_names(&mut csv_reader)?;
+        // Schema column index of each field, known after the first record without a header row
+        let mut column_indexes: Option<Vec<usize>> = match &header_names {
+            Some(header_names) => Some(Self::add_columns(schema, header_names, buf_name)?),
+            None => None,
+        };
 
         for result in csv_reader.records() {
             let record = result?;
-            if self.args.in_no_header && headers.is_empty() {
-                for header_index in 0..record.len() {
-                    headers.push(format!("c{}", header_index + 1));
-                }
-            }
-
-            let mut temp_record: Vec<Option<String>> = vec![];
-
-            for header_index in 0..headers.len() {
-                let data = record.get(header_index);
-                if data.is_some_and(|v| !v.is_empty()) {
-                    let data = data.context(format!("Cannot get a value at {}", header_index))?;
-                    let detected =
-                        SqliteDataType::detect_type(data, self.args.in_allow_leading_zeros);
-
-                    if temp_types
-                        .get(&header_index)
-                        .is_none_or(|v| v.can_convert_into(detected))
-                    {
-                        temp_types.insert(header_index, detected);
-                    }
-
-                    temp_record.push(Some(data.to_string()));
-                } else {
-                    temp_nullable.entry(header_index).or_insert(true);
-                    temp_record.push(None);
-                };
+            if column_indexes.is_none() {
+                let headers = Self::record_headers(&header_names, &record);
+                column_indexes = Some(Self::add_columns(schema, &headers, buf_name)?);
             }
 
-            temp_records.push(temp_record);
-            if let Some(position) = record.position() {
-                let position_byte = position.byte();
-                if position_byte % 1024 == 0 {
-                    pb.set_position(position_byte);
-                }
+            for (field_index, column_index) in column_indexes.iter().flatten().enumerate() {
+                let data = self.get_field(&record, field_index);
+                schema.observe(*column_index, data).with_context(|| {
+                    let line = record.position().map_or(0, |position| position.line());
+                    format!("Invalid data at line {} of {}", line, buf_name)
+                })?;
             }
+            schema.end_row();
         }
 
-        pb.set_length(temp_records.len() as u64);
-        pb.set_message("Generating SQL statements");
-        pb.set_position(0);
-
-        debug!("Finished parsing. Total rows: {}", temp_records.len());
-        let mut statements: Vec<String> = Vec::new();
-
-        let buf_name_quoted = SqliteQuoted::Field(buf_name.to_string()).get();
-
-        let mut sql_create: Vec<String> = vec![];
-        sql_create.push(format!("CREATE TABLE {} (", buf_name_quoted));
-        sql_create.push(format!(
-            "{} INTEGER PRIMARY KEY AUTOINCREMENT,",
-            SqliteQuoted::Field(self.args.raw_id.clone()).get()
-        ));
-
-        let mut sub_sql_create: Vec<String> = vec![];
-        let mut sub_sql_insert: Vec<String> = vec![];
-
-        for header_index in 0..headers.len() {
-            let header = headers
-                .get(header_index)
-                .context(format!("Header at pos {} not found", header_index))?;
-
-            let sqlite_type = temp_types
-                .get(&header_index)
-                .unwrap_or(&SqliteDataType::Text); // Fallback to Text
-
-            let not_null = if temp_nullable.contains_key(&header_index) {
-                ""
-            } else {
-                "NOT NULL"
-            };
+        Ok(())
+    }
 
-            let header_quoted = SqliteQuoted::Field(header.to_string()).get();
-            sub_sql_create.push(format!("{} {} {}", header_quoted, sqlite_type, not_null));
-            sub_sql_insert.push(header_quoted);
+    /// Adds the columns of one input to the schema
+    ///
+    /// # Returns
+    /// The schema column index of each field, or an error if a name is repeated.
+    fn add_columns(
+        schema: &mut TableSchema,
+        headers: &[String],
+        buf_name: &str,
+    ) -> anyhow::Result<Vec<usize>> {
+        let mut column_indexes: Vec<usize> = vec![];
+        for header in headers {
+            let column_index = schema.column_index(header);
+            if column_indexes.contains(&column_index) {
+                return Err(anyhow::anyhow!(
+                    "Duplicate column name \"{}\" in {}",
+                    header,
+                    buf_name
+                ));
+            }
+            column_indexes.push(column_index);
         }
+        Ok(column_indexes)
+    }
 
-        sql_create.push(format!("{});", sub_sql_create.join(",")));
-        let sql_create_final = sql_create.join("");
-        statements.push(sql_create_final);
-
-        // Insert values into the database table
-        let sql_insert = format!(
-            "INSERT INTO {} ({})",
-            buf_name_quoted,
-            sub_sql_insert.join(", ")
-        );
+    /// Inserts every record, matching fields to the schema columns by name
+    ///
+    /// # Returns
+    /// The number of rows inserted.
+    fn load<T: Read>(
+        &self,
+        mut csv_reader: CsvReaderType<T>,
+        schema: &TableSchema,
+        loader: &TableLoader,
+        source: Option<(usize, &str)>,
+    ) -> anyhow::Result<usize> {
+        let header_names = self.header_names(&mut csv_reader)?;
+        let mut column_indexes: Option<Vec<Option<usize>>> = None;
+
+        let mut row_count: usize = 0;
+        for result in csv_reader.records() {
+            let record = result?;
+            if column_indexes.is_none() {
+                let headers = Self::record_headers(&header_names, &record);
+                column_indexes = Some(headers.iter().map(|h| schema.position(h)).collect());
+            }
 
-        let chunk_size = 50;
-        let mut pb_current_position = 0;
-        let temp_records_iter = temp_records.chunks(chunk_size);
-        for records in temp_records_iter {
-            let mut sql_insert_values: Vec<String> = vec![];
-
-            for record in records {
-                let mut sql_insert_value: Vec<String> = vec![];
-
-                for element_index in 0..record.len() {
-                    let element = record
-                        .get(element_index)
-                        .context(format!("Element at pos {} not found", element_index))?;
-                    let sqlite_type = temp_types
-                        .get(&element_index)
-                        .unwrap_or(&SqliteDataType::Text); // Fallback to Text
-
-                    match element {
-                        None => {
-                            sql_insert_value.push("NULL".to_string());
-                        }
-                        Some(element) => {
-                            if *sqlite_type == SqliteDataType::Text {
-                                let text_quoted = SqliteQuoted::Text(element.to_string()).get();
-                                sql_insert_value.push(text_quoted);
-                            } else {
-                                sql_insert_value.push(element.to_string());
-                            }
-                        }
-                    }
+            let mut values: Vec<Option<&str>> = vec![None; schema.headers.len()];
+            for (field_index, column_index) in column_indexes.iter().flatten().enumerate() {
+                if let Some(column_index) = column_index {
+                    values[*column_index] = self.get_field(&record, field_index);
                 }
-
-                sql_insert_values.push(format!("({})", sql_insert_value.join(", ")));
             }
-
-            let sql_final_insert =
-                format!("{} VALUES {};", sql_insert, sql_insert_values.join(", "));
-            statements.push(sql_final_insert);
-
-            pb_current_position += chunk_size;
-            if pb_current_position % 10 == 0 {
-                pb.set_position(pb_current_position as u64);
+            if let Some((column_index, value)) = source {
+                values[column_index] = Some(value);
             }
+            loader.insert(&values)?;
+
+            row_count += 1;
         }
 
-        pb.finish_and_clear();
-        debug!("Finished parsing and loading data into table: {}", buf_name);
-        Ok((buf_name.to_string(), statements))
+        Ok(row_count)
+    }
+
+    /// Returns a field of the record, or `None` if it is missing, empty or a NULL token
+    fn get_field<'r>(&self, record: &'r csv::StringRecord, index: usize) -> Option<&'r str> {
+        record
+            .get(index)
+            .filter(|v| !v.is_empty() && !self.args.in_null.iter().any(|token| token == v))
     }
 
     /// Creates a CSV reader with specified options
     fn get_csv_reader<T: Read>(
         &self,
         buf_reader: std::io::BufReader<T>,
-        encoding: String,
+        encoding: &str,
         delimiter: u8,
     ) -> anyhow::Result<CsvReaderType<T>> {
         let encoding_label =
@@ -308,20 +370,4 @@ impl<'a> CsvParser<'a> {
 
         Ok(reader)
     }
-
-    /// Detects encoding from input
-    fn detect_encoding<T: Read>(
-        &self,
-        buf_reader: &mut std::io::BufReader<T>,
-    ) -> anyhow::Result<String> {
-        let mut buf = [0; 1024];
-
-        let _ = buf_reader.read(&mut buf)?;
-        let mut detector = chardetng::EncodingDetector::new();
-
-        detector.feed(&buf, false);
-        let name = detector.guess(None, true).name().to_string();
-
-        Ok(name)
-    }
 }
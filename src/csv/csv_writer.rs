@@ -1,20 +1,22 @@
 use crate::args_util::args::Args;
+use crate::args_util::blob_format::BlobFormat;
 use crate::args_util::is_tsv_filename::is_tsv_filename;
+use crate::db::sqlite_value::SqliteValue;
+use crate::output::output_stream::{OutputStream, open_output_stream};
+use crate::output::result_writer::ResultWriter;
 use anyhow::Context;
 use tracing::debug;
 
 /// Handles writing CSV data to stdout or a file with configurable settings.
 pub struct CsvWriter {
-    writer_stdout: Option<
-        csv::Writer<
-            encoding_rs_rw::EncodingWriter<encoding_rs_rw::misc::DefaultBuffer<std::io::Stdout>>,
-        >,
-    >,
-    writer_file: Option<
-        csv::Writer<
-            encoding_rs_rw::EncodingWriter<encoding_rs_rw::misc::DefaultBuffer<std::fs::File>>,
-        >,
-    >,
+    /// CSV writer; `None` after `finish`.
+    writer: Option<csv::Writer<OutputStream>>,
+    /// Skips the header row when `true`.
+    without_header: bool,
+    /// Text encoding for BLOB values.
+    blob_format: BlobFormat,
+    /// Text written for `NULL`.
+    null_token: String,
 }
 
 impl CsvWriter {
@@ -22,12 +24,6 @@ impl CsvWriter {
     pub fn new(args: &Args) -> anyhow::Result<Self> {
         debug!("Initializing CsvWriter with arguments: {:?}", args);
 
-        // Resolve encoding
-        let encoding_label =
-            encoding_rs::Encoding::for_label(args.out_encoding.to_lowercase().as_bytes())
-                .context(anyhow::anyhow!("Invalid encoding: {}", args.out_encoding))?;
-        let encoder = encoding_label.new_encoder();
-
         let mut writer = csv::WriterBuilder::new();
         let mut writer = writer
             .delimiter(args.out_delimiter)
@@ -41,93 +37,59 @@ impl CsvWriter {
             writer = writer.escape(out_escape);
         }
 
-        let writer_stdout;
-        let writer_file;
-
-        // Determine output target: stdout or file
-        match &args.out_file {
-            None => {
-                debug!("Output to stdout.");
-                let encoding_writer =
-                    encoding_rs_rw::EncodingWriter::new(std::io::stdout(), encoder);
-
-                writer_stdout = Some(writer.from_writer(encoding_writer));
-                writer_file = None;
-            }
-            Some(output_file) => {
-                debug!("Output to file: {}", output_file);
-                if is_tsv_filename(output_file) {
-                    debug!("Detected TSV file; setting delimiter to '\\t'.");
-                    writer.delimiter(b'\t');
-                }
-                let file = std::fs::File::create(output_file)?;
-                let encoding_writer = encoding_rs_rw::EncodingWriter::new(file, encoder);
-
-                writer_stdout = None;
-                writer_file = Some(writer.from_writer(encoding_writer));
-            }
-        };
+        if args.out_file.as_deref().is_some_and(is_tsv_filename) {
+            debug!("Detected TSV file; setting delimiter to '\\t'.");
+            writer.delimiter(b'\t');
+        }
 
         Ok(Self {
-            writer_stdout,
-            writer_file,
+            writer: Some(writer.from_writer(open_output_stream(args)?)),
+            without_header: args.out_without_header,
+            blob_format: args.out_blob_format,
+            null_token: args.out_null.clone(),
         })
     }
+}
 
-    /// Writes a single record to the output.
-    pub fn write_record<I, T>(&mut self, record: I) -> anyhow::Result<()>
-    where
-        I: IntoIterator<Item = T>,
-        T: AsRef<[u8]>,
-    {
-        if let Some(writer) = &mut self.writer_stdout {
-            writer
-                .write_record(record)
-                .context("Failed to write record to stdout")?;
-        } else if let Some(writer) = &mut self.writer_file {
-            writer
-                .write_record(record)
-                .context("Failed to write record to file")?;
-        } else {
-            anyhow::bail!("No available writer for output");
+impl ResultWriter for CsvWriter {
+    /// Writes the header row unless `--out-without-header` is given.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        _declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        if !self.without_header {
+            self.writer
+                .as_mut()
+                .context("Output is already finished")?
+                .write_record(header)
+                .context("Failed to write header")?;
         }
         Ok(())
     }
 
-    /// Writes multiple records in batch mode.
-    pub fn write_records<I, T>(&mut self, records: &Vec<I>) -> anyhow::Result<()>
-    where
-        I: IntoIterator<Item = T> + Clone,
-        T: AsRef<[u8]>,
-    {
-        if let Some(writer) = &mut self.writer_stdout {
-            for record in records {
-                writer
-                    .write_record(record.clone())
-                    .context("Failed to write record to stdout")?;
-            }
-        } else if let Some(writer) = &mut self.writer_file {
-            for record in records {
-                writer
-                    .write_record(record.clone())
-                    .context("Failed to write record to file")?;
-            }
-        } else {
-            anyhow::bail!("No available writer for output");
-        }
+    /// Writes a single record. `NULL` is written as `--out-null`, empty by default.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        self.writer
+            .as_mut()
+            .context("Output is already finished")?
+            .write_record(row.iter().map(|value| match value {
+                SqliteValue::Null => self.null_token.clone(),
+                other => other.to_text(self.blob_format),
+            }))
+            .context("Failed to write record")?;
         Ok(())
     }
 
-    /// Flushes the writer to ensure all data is written.
-    pub fn flush(&mut self) -> anyhow::Result<()> {
-        if let Some(writer) = &mut self.writer_stdout {
-            writer.flush().context("Failed to flush stdout writer")?;
-        } else if let Some(writer) = &mut self.writer_file {
-            writer.flush().context("Failed to flush file writer")?;
-        } else {
-            anyhow::bail!("No writer available to flush");
+    /// Flushes the writer and completes compressed output.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            let mut output_stream = writer
+                .into_inner()
+                .map_err(|err| err.into_error())
+                .context("Failed to flush writer")?;
+            output_stream.finish().context("Failed to finish output")?;
         }
-
         Ok(())
     }
 }
@@ -0,0 +1,341 @@
+use crate::args_util::args::Args;
+use crate::args_util::fixed_layout::FixedLayout;
+use crate::args_util::input_file::InputFile;
+use crate::db::detect_options::DetectOptions;
+use crate::db::table_loader::TableLoader;
+use crate::db::table_schema::TableSchema;
+use crate::format::STYLE_BAR;
+use crate::input::input_stream::{
+    detect_input_encoding, input_size, spool_stdin, with_input_stream,
+};
+use anyhow::Context;
+use std::io::{BufRead, Read};
+use tracing::debug;
+
+/// Parses fixed-width text, where each column takes the same positions on every
+/// line, and imports it into the SQLite database
+///
+/// Columns are cut at the positions of `--in-fixed-widths` or `--in-fixed-layout`,
+/// then trimmed with `--in-trim` and typed like CSV fields.
+#[derive(Debug)]
+pub struct FixedWidthParser<'a> {
+    /// Reference to application arguments
+    args: &'a Args,
+}
+
+impl<'a> FixedWidthParser<'a> {
+    /// Creates a new `FixedWidthParser` instance
+    pub fn new(args: &'a Args) -> Self {
+        Self { args }
+    }
+
+    /// Parses fixed-width text from standard input and imports it as the `stdin` table
+    ///
+    /// # Returns
+    /// The table name, or `None` if STDIN is interactive.
+    pub fn parse_stdin(&self, conn: &rusqlite::Connection) -> anyhow::Result<Option<String>> {
+        debug!("Parsing fixed-width stdin.");
+        let Some(spool) = spool_stdin()? else {
+            return Ok(None);
+        };
+
+        // Each pass reads the spooled content from the beginning
+        let input_file = InputFile {
+            path: spool.path().to_string_lossy().to_string(),
+            member: None,
+            table_name: None,
+        };
+        self.parse_file(conn, &input_file, "stdin")?;
+        Ok(Some("stdin".to_string()))
+    }
+
+    /// Parses a fixed-width file and imports it as the given table
+    pub fn parse_file(
+        &self,
+        conn: &rusqlite::Connection,
+        input_file: &InputFile,
+        table_name: &str,
+    ) -> anyhow::Result<()> {
+        debug!("Parsing fixed-width file: {}", input_file);
+
+        // First pass: infer the column layout
+        let mut schema = TableSchema::new(
+            DetectOptions::from(self.args),
+            self.args.in_schema.table(table_name),
+        );
+        self.scan_file(input_file, &mut schema)?;
+        schema.finish(table_name)?;
+
+        // Second pass: create the table and insert rows
+        let loader = TableLoader::new(conn, table_name, &self.args.raw_id, &schema)?;
+        let row_count = self.load_file(input_file, &schema, &loader, None)?;
+        loader.finish()?;
+
+        debug!(
+            "Finished parsing and loading data into table: {}, rows: {}",
+            table_name, row_count
+        );
+        Ok(())
+    }
+
+    /// Scans a file into a schema shared by several inputs
+    pub fn scan_file(
+        &self,
+        input_file: &InputFile,
+        schema: &mut TableSchema,
+    ) -> anyhow::Result<()> {
+        let encoding = self.encoding(input_file)?;
+        let pb = Self::progress_bar(input_size(input_file)?);
+        pb.set_message(format!("Scanning fixed-width: {}", input_file));
+
+        with_input_stream(input_file, &pb, |reader| {
+            let mut reader = self.reader(reader, encoding)?;
+            let mut column_indexes: Vec<usize> = vec![];
+            for header in self.headers(&mut reader)? {
+                let column_index = schema.column_index(&header);
+                if column_indexes.contains(&column_index) {
+                    return Err(anyhow::anyhow!(
+                        "Duplicate column name \"{}\" in {}",
+                        header,
+                        input_file
+                    ));
+                }
+                column_indexes.push(column_index);
+            }
+
+            while let Some(fields) = reader.next_record()? {
+                for (field, column_index) in fields.iter().zip(&column_indexes) {
+                    schema
+                        .observe(*column_index, self.get_field(field))
+                        .with_context(|| {
+                            format!(
+                                "Invalid data at line {} of {}",
+                                reader.line_number, input_file
+                            )
+                        })?;
+                }
+                schema.end_row();
+            }
+            Ok(())
+        })?;
+        pb.finish_and_clear();
+        Ok(())
+    }
+
+    /// Inserts the records of a file scanned by `scan_file`
+    ///
+    /// # Arguments
+    /// * `source` - Column index and value filled in for every record, e.g. the file name.
+    ///
+    /// # Returns
+    /// The number of rows inserted.
+    pub fn load_file(
+        &self,
+        input_file: &InputFile,
+        schema: &TableSchema,
+        loader: &TableLoader,
+        source: Option<(usize, &str)>,
+    ) -> anyhow::Result<usize> {
+        let encoding = self.encoding(input_file)?;
+        let pb = Self::progress_bar(input_size(input_file)?);
+        pb.set_message(format!("Importing data from {}", input_file));
+
+        let row_count = with_input_stream(input_file, &pb, |reader| {
+            let mut reader = self.reader(reader, encoding)?;
+            let column_indexes: Vec<Option<usize>> = self
+                .headers(&mut reader)?
+                .iter()
+                .map(|header| schema.position(header))
+                .collect();
+
+            let mut row_count: usize = 0;
+            while let Some(fields) = reader.next_record()? {
+                let mut values: Vec<Option<&str>> = vec![None; schema.headers.len()];
+                for (field, column_index) in fields.iter().zip(&column_indexes) {
+                    if let Some(column_index) = column_index {
+                        values[*column_index] = self.get_field(field);
+                    }
+                }
+                if let Some((column_index, value)) = source {
+                    values[column_index] = Some(value);
+                }
+                loader.insert(&values)?;
+                row_count += 1;
+            }
+            Ok(row_count)
+        })?;
+        pb.finish_and_clear();
+        Ok(row_count)
+    }
+
+    /// Creates a progress bar for content of the given size in bytes
+    fn progress_bar(buf_size: u64) -> indicatif::ProgressBar {
+        let pb = indicatif::ProgressBar::new(buf_size);
+        pb.set_style(STYLE_BAR.clone());
+        pb
+    }
+
+    /// Detects the character encoding of the input
+    fn encoding(&self, input_file: &InputFile) -> anyhow::Result<&'static encoding_rs::Encoding> {
+        let encoding = detect_input_encoding(input_file)?;
+        debug!("Detected encoding: {} for file: {}", encoding, input_file);
+        encoding_rs::Encoding::for_label_no_replacement(encoding.to_lowercase().as_bytes())
+            .context(anyhow::anyhow!("Invalid encoding: {}", encoding))
+    }
+
+    /// Creates a reader cutting the lines of the content into fields
+    fn reader<'r>(
+        &'r self,
+        reader: &'r mut dyn Read,
+        encoding: &'static encoding_rs::Encoding,
+    ) -> anyhow::Result<FixedWidthReader<'r>> {
+        let layout = self.args.in_fixed_layout.as_ref().context(
+            "Fixed-width input needs column positions. Give them with --in-fixed-widths or --in-fixed-layout.",
+        )?;
+        Ok(FixedWidthReader {
+            reader: std::io::BufReader::new(reader),
+            layout,
+            encoding,
+            in_bytes: self.args.in_fixed_bytes,
+            comment: self.args.in_comment,
+            line: vec![],
+            line_number: 0,
+        })
+    }
+
+    /// Returns the column names: from the layout file, the header line, or `c1`, `c2`, ...
+    ///
+    /// The header line is consumed unless `--in-no-header` is given, also when
+    /// the names come from the layout file.
+    fn headers(&self, reader: &mut FixedWidthReader) -> anyhow::Result<Vec<String>> {
+        let header_line = if self.args.in_no_header {
+            None
+        } else {
+            reader.next_record()?
+        };
+        let trim = matches!(self.args.in_trim, csv::Trim::Headers | csv::Trim::All);
+        Ok(reader
+            .layout
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let name = match (&column.name, &header_line) {
+                    (Some(name), _) => name.to_string(),
+                    (None, Some(header_line)) if trim => header_line[index].trim().to_string(),
+                    (None, Some(header_line)) => header_line[index].to_string(),
+                    (None, None) => String::new(),
+                };
+                if name.is_empty() {
+                    format!("c{}", index + 1)
+                } else {
+                    name
+                }
+            })
+            .collect())
+    }
+
+    /// Returns a field trimmed with `--in-trim`, or `None` if it is empty or a NULL token
+    fn get_field<'f>(&self, field: &'f str) -> Option<&'f str> {
+        let field = if matches!(self.args.in_trim, csv::Trim::Fields | csv::Trim::All) {
+            field.trim()
+        } else {
+            field
+        };
+        Some(field).filter(|v| !v.is_empty() && !self.args.in_null.iter().any(|token| token == v))
+    }
+}
+
+/// Reads the lines of fixed-width content and cuts them into fields
+struct FixedWidthReader<'r> {
+    reader: std::io::BufReader<&'r mut dyn Read>,
+    layout: &'r FixedLayout,
+    encoding: &'static encoding_rs::Encoding,
+    /// Cuts at byte positions of the encoded line when `true`, at characters otherwise.
+    in_bytes: bool,
+    /// Lines starting with this byte are skipped.
+    comment: Option<u8>,
+    /// Buffer of the current line.
+    line: Vec<u8>,
+    /// Line number of the last record, starting at 1.
+    line_number: usize,
+}
+
+impl FixedWidthReader<'_> {
+    /// Reads the next record, skipping blank and comment lines
+    ///
+    /// # Returns
+    /// One field per layout column, empty where the line is too short, or `None` at the end.
+    fn next_record(&mut self) -> anyhow::Result<Option<Vec<String>>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+
+            let mut line = self.line.as_slice();
+            if self.line_number == 1 && self.encoding == encoding_rs::UTF_8 {
+                line = line.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(line);
+            }
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() || self.comment.is_some_and(|comment| line[0] == comment) {
+                continue;
+            }
+
+            return Ok(Some(if self.in_bytes {
+                cut_bytes(line, self.layout, self.encoding)
+            } else {
+                let (text, _) = self.encoding.decode_without_bom_handling(line);
+                cut_chars(&text, self.layout)
+            }));
+        }
+    }
+}
+
+/// Cuts a decoded line at character positions
+fn cut_chars(line: &str, layout: &FixedLayout) -> Vec<String> {
+    // Byte offset of every character, and of the end of the line
+    let offsets: Vec<usize> = line
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain([line.len()])
+        .collect();
+    let offset = |position: usize| offsets[position.min(offsets.len() - 1)];
+    layout
+        .columns
+        .iter()
+        .map(|column| line[offset(column.start)..offset(column.end())].to_string())
+        .collect()
+}
+
+/// Cuts a line at byte positions, then decodes each field
+fn cut_bytes(
+    line: &[u8],
+    layout: &FixedLayout,
+    encoding: &'static encoding_rs::Encoding,
+) -> Vec<String> {
+    layout
+        .columns
+        .iter()
+        .map(|column| {
+            let field = &line[column.start.min(line.len())..column.end().min(line.len())];
+            encoding.decode_without_bom_handling(field).0.into_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn test_cut_fields() {
+    let layout = FixedLayout::from_widths("2,3,4").unwrap();
+    assert_eq!(cut_chars("ab東京都x", &layout), vec!["ab", "東京都", "x"]);
+    assert_eq!(cut_chars("a", &layout), vec!["a", "", ""]);
+
+    let (line, _, _) = encoding_rs::SHIFT_JIS.encode("ab東京x");
+    let layout = FixedLayout::from_widths("2,4,1").unwrap();
+    assert_eq!(
+        cut_bytes(&line, &layout, encoding_rs::SHIFT_JIS),
+        vec!["ab", "東京", "x"]
+    );
+}
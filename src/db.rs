@@ -1,9 +1,17 @@
+mod add_regexp_function;
+pub mod detect_options;
 pub mod execute_statements;
+pub mod inferred_type;
+pub mod initialize_database;
+pub mod is_reserved_table_name;
 pub mod list_available_table;
-pub mod statement_result;
+pub mod schema_overrides;
 pub mod sqlite_data_type;
 pub mod sqlite_quoted;
-pub mod is_reserved_table_name;
+pub mod sqlite_value;
+pub mod statement_result;
 pub mod table_info;
-pub mod initialize_database;
-mod add_regexp_function;
+pub mod table_loader;
+pub mod table_name_from_path;
+pub mod table_schema;
+pub mod temporal_value;
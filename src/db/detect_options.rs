@@ -0,0 +1,39 @@
+use crate::args_util::args::Args;
+use crate::args_util::date_storage::DateStorage;
+use crate::args_util::number_locale::NumberLocale;
+
+/// Settings for detecting column types and converting input values.
+#[derive(Debug, Clone, Default)]
+pub struct DetectOptions {
+    /// Whether numbers with leading zeros are allowed.
+    pub allow_leading_zeros: bool,
+
+    /// Grouping separators and decimal mark of numbers, if not plain.
+    pub number_locale: Option<NumberLocale>,
+
+    /// Whether boolean values are detected.
+    pub detect_boolean: bool,
+
+    /// Whether dates, datetimes and times are detected.
+    pub detect_dates: bool,
+
+    /// Date formats replacing the built-in ones, if any.
+    pub date_formats: Vec<String>,
+
+    /// Storage of dates and datetimes.
+    pub date_storage: DateStorage,
+}
+
+impl From<&Args> for DetectOptions {
+    fn from(args: &Args) -> Self {
+        Self {
+            allow_leading_zeros: args.in_allow_leading_zeros,
+            number_locale: args.in_number_locale,
+            detect_boolean: args.in_detect_boolean,
+            // Formats given for input dates imply their detection
+            detect_dates: args.in_detect_dates || !args.in_date_format.is_empty(),
+            date_formats: args.in_date_format.clone(),
+            date_storage: args.in_date_storage,
+        }
+    }
+}
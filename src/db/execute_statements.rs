@@ -1,4 +1,8 @@
+use crate::args_util::sql_script::ScriptStatement;
+use crate::db::sqlite_value::SqliteValue;
 use crate::db::statement_result::StatementResult;
+use crate::output::result_writer::ResultWriter;
+use crate::output::routed_outputs::RoutedOutputs;
 
 use tracing::debug;
 
@@ -9,15 +13,17 @@ use tracing::debug;
 ///
 /// # Arguments
 /// * `pool` - SQLite connection pool.
-/// * `statements` - SQL statements to execute.
+/// * `statements` - SQL statements to execute, with the outputs of their annotations.
 /// * `raw_id` - Optional identifier for internal column filtering.
+/// * `routed_outputs` - Writes query results routed by annotations; `None` ignores the annotations.
 ///
 /// # Returns
 /// A vector of `StatementResult` with execution details.
 pub fn execute_statements(
     pool: &r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>,
-    statements: Vec<sqlparser::ast::Statement>,
+    statements: Vec<ScriptStatement>,
     raw_id: Option<String>,
+    mut routed_outputs: Option<&mut RoutedOutputs>,
 ) -> anyhow::Result<Vec<StatementResult>> {
     let conn = pool.get()?;
 
@@ -25,111 +31,40 @@ pub fn execute_statements(
     let mut statement_results: Vec<StatementResult> = vec![];
     let raw_id = raw_id.unwrap_or("_raw_id".to_string());
 
-    for statement in statements {
+    for ScriptStatement { statement, output } in statements {
         let mut statement_result = StatementResult::default();
 
         match statement {
-            sqlparser::ast::Statement::Query(_)
-            | sqlparser::ast::Statement::Pragma { is_eq: false, .. } => {
+            _ if is_query(&statement) => {
                 debug!("Running query/pragma: {}", statement);
 
                 let start = std::time::Instant::now();
                 let mut stmt = conn.prepare(&statement.to_string())?;
-                let temp_column_names = stmt.column_names();
-
-                let mut i = 0;
-                let mut column_names_map: std::collections::HashMap<usize, String> =
-                    std::collections::HashMap::new();
-                temp_column_names.iter().for_each(|v| {
-                    column_names_map.insert(i, v.to_string());
-                    i += 1;
-                });
-
-                let excluded_index: Vec<usize> = column_names_map
-                    .iter()
-                    .filter(|v| v.1.eq(&raw_id))
-                    .map(|v| *v.0)
-                    .collect();
-                let column_count = temp_column_names.len();
-
-                let mut column_names: Vec<String> = vec![];
-                for index in 0..column_count {
-                    if excluded_index.contains(&index) {
-                        continue;
-                    }
-                    if let Some(found) = column_names_map.get(&index) {
-                        column_names.push(found.to_string());
-                    }
-                }
-                statement_result.header = column_names;
+                let columns = QueryColumns::new(&stmt, &raw_id);
 
                 let mut rows = stmt.query([])?;
                 let mut row_index: usize = 0;
 
                 while let Some(row) = rows.next()? {
-                    let mut values: Vec<String> = vec![];
-
-                    for index in 0..column_count {
-                        if excluded_index.contains(&index) {
-                            continue;
-                        }
-
-                        let null = "".to_string();
-
-                        let value: rusqlite::Result<Option<String>> = row.get(index);
-                        if let Ok(value) = value {
-                            match value {
-                                None => {
-                                    values.push(null);
-                                }
-                                Some(value) => {
-                                    values.push(value);
-                                }
-                            }
-                            continue;
-                        }
-
-                        let value: rusqlite::Result<Option<i64>> = row.get(index);
-                        if let Ok(value) = value {
-                            match value {
-                                None => {
-                                    values.push(null);
-                                }
-                                Some(value) => {
-                                    values.push(value.to_string());
-                                }
-                            }
-                            continue;
-                        }
-
-                        let value: rusqlite::Result<Option<f64>> = row.get(index);
-                        if let Ok(value) = value {
-                            match value {
-                                None => {
-                                    values.push(null);
-                                }
-                                Some(value) => {
-                                    values.push(value.to_string());
-                                }
-                            }
-                            continue;
-                        }
-
-                        anyhow::bail!(
-                            "Invalid data type at row:{} col:{}",
-                            row_index.saturating_add(1),
-                            index.saturating_add(1)
-                        );
-                    }
-                    statement_result.rows.push(values);
+                    statement_result.rows.push(columns.values(row)?);
                     row_index = row_index.saturating_add(1);
                 }
+                statement_result.header = columns.header;
+                statement_result.declared_types = columns.declared_types;
                 statement_result.elapsed = start.elapsed();
+                statement_result.is_query = true;
 
                 debug!(
                     "Query completed. Rows: {}, Time: {:?}",
                     row_index, statement_result.elapsed
                 );
+
+                if let (Some(routed_outputs), Some(output)) =
+                    (routed_outputs.as_deref_mut(), output)
+                {
+                    routed_outputs.write(&output, &statement_result)?;
+                    statement_result.output = Some(output);
+                }
             }
             other_statement => {
                 debug!("Executing non-query statement: {}", other_statement);
@@ -145,13 +80,14 @@ pub fn execute_statements(
                 let result = conn.execute(&other_statement.to_string(), [])?;
 
                 let mut header = vec!["executed_statement".to_string()];
-                let mut row = vec![other_statement.to_string()];
+                let mut row = vec![SqliteValue::Text(other_statement.to_string())];
 
                 if affected_rows_enabled {
                     header.push("affected_rows".to_string());
-                    row.push(result.to_string());
+                    row.push(SqliteValue::Integer(result as i64));
                 }
 
+                statement_result.declared_types = vec![None; header.len()];
                 statement_result.header = header;
                 statement_result.rows = vec![row];
                 statement_result.elapsed = start.elapsed();
@@ -171,3 +107,172 @@ pub fn execute_statements(
     );
     Ok(statement_results)
 }
+
+/// Executes a query and writes its rows as they are read, so the result is
+/// never held in memory as a whole.
+///
+/// The writer is created only once the statement is prepared, so an invalid
+/// query leaves an existing output file untouched.
+///
+/// # Arguments
+/// * `pool` - SQLite connection pool.
+/// * `statement` - Query or PRAGMA statement to execute.
+/// * `raw_id` - Identifier of the internal column left out of the output.
+/// * `create_writer` - Opens the output of the result; finished after the last row.
+///
+/// # Returns
+/// The number of rows written.
+pub fn stream_statement(
+    pool: &r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>,
+    statement: &sqlparser::ast::Statement,
+    raw_id: &str,
+    create_writer: impl FnOnce() -> anyhow::Result<Box<dyn ResultWriter>>,
+) -> anyhow::Result<usize> {
+    let conn = pool.get()?;
+    debug!("Streaming query/pragma: {}", statement);
+
+    let start = std::time::Instant::now();
+    let mut stmt = conn.prepare(&statement.to_string())?;
+    let columns = QueryColumns::new(&stmt, raw_id);
+
+    let mut writer = create_writer()?;
+    writer.write_header(&columns.header, &columns.declared_types)?;
+
+    let mut rows = stmt.query([])?;
+    let mut row_count: usize = 0;
+    while let Some(row) = rows.next()? {
+        writer.write_row(&columns.values(row)?)?;
+        row_count = row_count.saturating_add(1);
+    }
+    writer.finish()?;
+
+    debug!(
+        "Query completed. Rows: {}, Time: {:?}",
+        row_count,
+        start.elapsed()
+    );
+    Ok(row_count)
+}
+
+/// Returns `true` for statements that return rows: queries and PRAGMA reads.
+pub fn is_query(statement: &sqlparser::ast::Statement) -> bool {
+    matches!(
+        statement,
+        sqlparser::ast::Statement::Query(_)
+            | sqlparser::ast::Statement::Pragma { is_eq: false, .. }
+    )
+}
+
+/// Columns of a query result, leaving out the `raw_id` column.
+struct QueryColumns {
+    /// Positions of the output columns in the statement.
+    indexes: Vec<usize>,
+    /// Column names.
+    header: Vec<String>,
+    /// Declared types of the columns; `None` for expressions.
+    declared_types: Vec<Option<String>>,
+}
+
+impl QueryColumns {
+    /// Reads the columns of a prepared statement.
+    fn new(stmt: &rusqlite::Statement, raw_id: &str) -> Self {
+        let columns = stmt.columns();
+        let indexes: Vec<usize> = columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| column.name() != raw_id)
+            .map(|(index, _)| index)
+            .collect();
+        Self {
+            header: indexes
+                .iter()
+                .map(|index| columns[*index].name().to_string())
+                .collect(),
+            declared_types: indexes
+                .iter()
+                .map(|index| columns[*index].decl_type().map(str::to_string))
+                .collect(),
+            indexes,
+        }
+    }
+
+    /// Copies the values of the output columns from a row.
+    fn values(&self, row: &rusqlite::Row) -> rusqlite::Result<Vec<SqliteValue>> {
+        self.indexes
+            .iter()
+            .map(|index| Ok(SqliteValue::from(row.get_ref(*index)?)))
+            .collect()
+    }
+}
+
+#[test]
+fn test_stream_statement() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records everything written to it.
+    #[derive(Default)]
+    struct Written {
+        header: Vec<String>,
+        rows: Vec<Vec<SqliteValue>>,
+        finished: bool,
+    }
+    struct CollectingWriter(Rc<RefCell<Written>>);
+    impl ResultWriter for CollectingWriter {
+        fn write_header(&mut self, header: &[String], _: &[Option<String>]) -> anyhow::Result<()> {
+            self.0.borrow_mut().header = header.to_vec();
+            Ok(())
+        }
+        fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+            self.0.borrow_mut().rows.push(row.to_vec());
+            Ok(())
+        }
+        fn finish(&mut self) -> anyhow::Result<()> {
+            self.0.borrow_mut().finished = true;
+            Ok(())
+        }
+    }
+
+    let pool = r2d2::Pool::builder()
+        .max_size(1)
+        .build(r2d2_sqlite::SqliteConnectionManager::memory())
+        .unwrap();
+    pool.get()
+        .unwrap()
+        .execute_batch(
+            "CREATE TABLE t (_raw_id INTEGER PRIMARY KEY, a TEXT, b INTEGER);
+             INSERT INTO t (a, b) VALUES ('x', 1), (NULL, 2);",
+        )
+        .unwrap();
+    let parse = |sql: &str| {
+        sqlparser::parser::Parser::parse_sql(&sqlparser::dialect::SQLiteDialect {}, sql)
+            .unwrap()
+            .remove(0)
+    };
+
+    let written = Rc::new(RefCell::new(Written::default()));
+    let row_count = stream_statement(
+        &pool,
+        &parse("SELECT * FROM t ORDER BY _raw_id"),
+        "_raw_id",
+        || Ok(Box::new(CollectingWriter(written.clone()))),
+    )
+    .unwrap();
+    let written = written.borrow();
+    assert_eq!(row_count, 2);
+    assert_eq!(written.header, vec!["a", "b"]);
+    assert_eq!(
+        written.rows,
+        vec![
+            vec![SqliteValue::Text("x".to_string()), SqliteValue::Integer(1)],
+            vec![SqliteValue::Null, SqliteValue::Integer(2)],
+        ]
+    );
+    assert!(written.finished);
+
+    // An invalid query fails before the writer is created
+    let result = stream_statement(&pool, &parse("SELECT missing FROM t"), "_raw_id", || {
+        panic!("The writer must not be created")
+    });
+    assert!(result.is_err());
+}
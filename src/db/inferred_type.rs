@@ -0,0 +1,53 @@
+use crate::db::sqlite_value::SqliteValue;
+
+/// Type of a result column without a declared type, such as an expression,
+/// inferred from its values.
+///
+/// Writers that fix column types up front see only the first rows of a
+/// streamed result, so numbers always take the widest numeric type: a column
+/// of integers may still receive a `REAL` later on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InferredType {
+    /// `INTEGER` and `REAL` values.
+    Numeric,
+    Text,
+    Blob,
+}
+
+impl InferredType {
+    /// Infers the type of a column from its values, ignoring `NULL`.
+    ///
+    /// # Returns
+    /// The type shared by the values, `Text` for a mix of storage classes, or
+    /// `None` if every value is `NULL`.
+    pub fn infer<'a>(values: impl Iterator<Item = &'a SqliteValue>) -> Option<Self> {
+        let mut inferred_type: Option<Self> = None;
+        for value in values {
+            let value_type = match value {
+                SqliteValue::Null => continue,
+                SqliteValue::Integer(_) | SqliteValue::Real(_) => InferredType::Numeric,
+                SqliteValue::Blob(_) => InferredType::Blob,
+                SqliteValue::Text(_) => return Some(InferredType::Text),
+            };
+            match inferred_type {
+                Some(current) if current != value_type => return Some(InferredType::Text),
+                _ => inferred_type = Some(value_type),
+            }
+        }
+        inferred_type
+    }
+}
+
+#[test]
+fn test_infer() {
+    let infer = |values: &[SqliteValue]| InferredType::infer(values.iter());
+    assert_eq!(
+        infer(&[SqliteValue::Integer(1), SqliteValue::Null]),
+        Some(InferredType::Numeric)
+    );
+    assert_eq!(
+        infer(&[SqliteValue::Blob(vec![0]), SqliteValue::Integer(1)]),
+        Some(InferredType::Text)
+    );
+    assert_eq!(infer(&[SqliteValue::Null]), None);
+}
@@ -0,0 +1,151 @@
+use crate::db::sqlite_data_type::SqliteDataType;
+use anyhow::Context;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Column definition given with `--in-schema` or `--in-type`, applied instead of inference.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnOverride {
+    /// Data type of the column. Values that do not fit fail the import.
+    #[serde(rename = "type", default, deserialize_with = "deserialize_data_type")]
+    pub data_type: Option<SqliteDataType>,
+
+    /// `true` declares the column `NOT NULL`, `false` allows `NULL` even without empty values.
+    pub not_null: Option<bool>,
+
+    /// Value stored for empty cells, also declared as the column `DEFAULT`.
+    #[serde(default, deserialize_with = "deserialize_default")]
+    pub default: Option<String>,
+
+    /// Column name in the table.
+    pub rename: Option<String>,
+}
+
+/// Column overrides keyed by table name, then by column name in the input.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaOverrides {
+    tables: HashMap<String, HashMap<String, ColumnOverride>>,
+}
+
+impl SchemaOverrides {
+    /// Loads a schema file. `.json` files are read as JSON, other files as TOML.
+    ///
+    /// ```toml
+    /// ["orders.csv".zip]
+    /// type = "TEXT"
+    /// rename = "postal_code"
+    /// ```
+    pub fn load(file_path: &str) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(file_path)
+            .context(format!("Failed to read schema file {}", file_path))?;
+        let tables = if file_path.to_lowercase().ends_with(".json") {
+            serde_json::from_str(&content).context(format!("Invalid schema file {}", file_path))?
+        } else {
+            toml::from_str(&content).context(format!("Invalid schema file {}", file_path))?
+        };
+        Ok(Self { tables })
+    }
+
+    /// Adds column types given as `table:column=TYPE,column=TYPE`.
+    ///
+    /// Types given here replace the ones from the schema file.
+    pub fn add_inline(&mut self, spec: &str) -> anyhow::Result<()> {
+        let invalid = || {
+            format!(
+                "Invalid --in-type '{}'. Expected 'table:column=TYPE,...'",
+                spec
+            )
+        };
+        let (table_name, columns) = spec.rsplit_once(':').with_context(invalid)?;
+        let table = self.tables.entry(table_name.to_string()).or_default();
+        for column in columns.split(',') {
+            let (column_name, data_type) = column.split_once('=').with_context(invalid)?;
+            let data_type = SqliteDataType::from_str(data_type.trim()).with_context(invalid)?;
+            table
+                .entry(column_name.trim().to_string())
+                .or_default()
+                .data_type = Some(data_type);
+        }
+        Ok(())
+    }
+
+    /// Returns the column overrides of a table, keyed by column name in the input.
+    pub fn table(&self, table_name: &str) -> HashMap<String, ColumnOverride> {
+        self.tables.get(table_name).cloned().unwrap_or_default()
+    }
+
+    /// Returns the tables with overrides that are not among `table_names`, sorted by name.
+    pub fn unused_tables(&self, table_names: &[String]) -> Vec<&str> {
+        let mut unused_tables: Vec<&str> = self
+            .tables
+            .keys()
+            .filter(|table_name| !table_names.contains(table_name))
+            .map(String::as_str)
+            .collect();
+        unused_tables.sort();
+        unused_tables
+    }
+}
+
+/// Reads a type name such as `INTEGER` or `text`.
+fn deserialize_data_type<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<SqliteDataType>, D::Error> {
+    let name: String = serde::Deserialize::deserialize(deserializer)?;
+    SqliteDataType::from_str(&name)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Reads a default value given as a string, number or boolean.
+fn deserialize_default<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let value: serde_json::Value = serde::Deserialize::deserialize(deserializer)?;
+    match value {
+        serde_json::Value::String(value) => Ok(Some(value)),
+        serde_json::Value::Number(value) => Ok(Some(value.to_string())),
+        serde_json::Value::Bool(value) => Ok(Some(if value { "1" } else { "0" }.to_string())),
+        other => Err(serde::de::Error::custom(format!(
+            "expected a string or number, found {}",
+            other
+        ))),
+    }
+}
+
+#[test]
+fn test_schema_overrides() {
+    let mut overrides = SchemaOverrides {
+        tables: toml::from_str(
+            r#"
+            ["orders.csv".zip]
+            type = "TEXT"
+            not_null = true
+            default = 0
+            rename = "postal_code"
+            "#,
+        )
+        .unwrap(),
+    };
+    overrides
+        .add_inline("orders.csv:amount=REAL, zip=integer")
+        .unwrap();
+
+    let table = overrides.table("orders.csv");
+    let zip = &table["zip"];
+    assert_eq!(zip.data_type, Some(SqliteDataType::Integer));
+    assert_eq!(zip.not_null, Some(true));
+    assert_eq!(zip.default.as_deref(), Some("0"));
+    assert_eq!(zip.rename.as_deref(), Some("postal_code"));
+    assert_eq!(table["amount"].data_type, Some(SqliteDataType::Real));
+    assert!(overrides.table("other.csv").is_empty());
+    assert!(
+        overrides
+            .unused_tables(&["orders.csv".to_string()])
+            .is_empty()
+    );
+    assert_eq!(overrides.unused_tables(&[]), vec!["orders.csv"]);
+    assert!(overrides.add_inline("orders.csv:amount=MONEY").is_err());
+    assert!(overrides.add_inline("amount=REAL").is_err());
+}
@@ -1,12 +1,22 @@
+use crate::db::detect_options::DetectOptions;
+use crate::db::temporal_value::TemporalValue;
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 /// SQLite data types for mapping CSV data to SQLite-compatible types.
+///
+/// `Boolean`, `Date`, `Datetime` and `Time` are logical types. They are declared
+/// as such in `CREATE TABLE` and stored as text or integers.
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum SqliteDataType {
     Integer,
     Real,
     Text,
+    Boolean,
+    Date,
+    Datetime,
+    Time,
 }
 
 impl Display for SqliteDataType {
@@ -16,23 +26,125 @@ impl Display for SqliteDataType {
             SqliteDataType::Integer => "INTEGER",
             SqliteDataType::Real => "REAL",
             SqliteDataType::Text => "TEXT",
+            SqliteDataType::Boolean => "BOOLEAN",
+            SqliteDataType::Date => "DATE",
+            SqliteDataType::Datetime => "DATETIME",
+            SqliteDataType::Time => "TIME",
         };
         write!(f, "{}", str)
     }
 }
 
+impl FromStr for SqliteDataType {
+    type Err = anyhow::Error;
+
+    /// Parses a type name, ignoring case. Common aliases such as `INT` and `TIMESTAMP` are accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "INTEGER" | "INT" => Ok(SqliteDataType::Integer),
+            "REAL" | "FLOAT" | "DOUBLE" => Ok(SqliteDataType::Real),
+            "TEXT" => Ok(SqliteDataType::Text),
+            "BOOLEAN" | "BOOL" => Ok(SqliteDataType::Boolean),
+            "DATE" => Ok(SqliteDataType::Date),
+            "DATETIME" | "TIMESTAMP" => Ok(SqliteDataType::Datetime),
+            "TIME" => Ok(SqliteDataType::Time),
+            _ => Err(anyhow::anyhow!(
+                "Unknown data type '{}'. Expected one of INTEGER, REAL, TEXT, BOOLEAN, DATE, DATETIME, TIME",
+                s
+            )),
+        }
+    }
+}
+
 impl SqliteDataType {
-    /// Checks if conversion between types is valid based on SQLite rules.
+    /// Returns the type of a column declared in `CREATE TABLE`, e.g. by `PRAGMA table_info`.
+    ///
+    /// Type names csvs uses are matched exactly, others by SQLite's affinity
+    /// rules, so `VARCHAR(20)` is `Text` and `BIGINT` is `Integer`.
+    ///
+    /// # Returns
+    /// The type, or `None` for `NUMERIC` and other declarations without a matching type.
+    pub fn from_declared_type(declared_type: &str) -> Option<Self> {
+        if let Ok(data_type) = Self::from_str(declared_type.trim()) {
+            return Some(data_type);
+        }
+        let declared_type = declared_type.to_uppercase();
+        if declared_type.contains("INT") {
+            Some(Self::Integer)
+        } else if ["CHAR", "CLOB", "TEXT"]
+            .iter()
+            .any(|name| declared_type.contains(name))
+        {
+            Some(Self::Text)
+        } else if ["REAL", "FLOA", "DOUB"]
+            .iter()
+            .any(|name| declared_type.contains(name))
+        {
+            Some(Self::Real)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the type of a column holding values of both types.
+    ///
+    /// - Equal types are kept.
+    /// - `Integer` and `Real` become `Real`.
+    /// - `Boolean` and `Integer` or `Real` become the numeric type.
+    /// - `Date` and `Datetime` become `Datetime`.
+    /// - Any other combination becomes `Text`.
+    pub fn merge(&self, other: SqliteDataType) -> SqliteDataType {
+        use SqliteDataType::*;
+        match (*self, other) {
+            (a, b) if a == b => a,
+            (Integer, Real) | (Real, Integer) => Real,
+            (Boolean, number @ (Integer | Real)) | (number @ (Integer | Real), Boolean) => number,
+            (Date, Datetime) | (Datetime, Date) => Datetime,
+            _ => Text,
+        }
+    }
+
+    /// Returns `true` if the value can be stored as this type without falling back to text.
+    pub fn accepts(&self, source: &str, options: &DetectOptions) -> bool {
+        match self {
+            SqliteDataType::Integer => normalize_number(source, options)
+                .is_some_and(|number| i64::from_str(&number).is_ok()),
+            SqliteDataType::Real => normalize_number(source, options)
+                .is_some_and(|number| f64::from_str(&number).is_ok()),
+            SqliteDataType::Text => true,
+            SqliteDataType::Boolean => parse_boolean(source).is_some(),
+            SqliteDataType::Date | SqliteDataType::Datetime | SqliteDataType::Time => {
+                TemporalValue::parse(source, &options.date_formats)
+                    .is_some_and(|value| self.merge(value.data_type()) == *self)
+            }
+        }
+    }
+
+    /// Converts a string into a value bindable to a column of this type.
     ///
-    /// Conversion Matrix:
-    /// - `Integer` can convert to any type.
-    /// - `Real` can convert to `Real` or `Text`.
-    /// - `Text` can only remain `Text`.
-    pub fn can_convert_into(&self, to: SqliteDataType) -> bool {
+    /// Numbers are normalized according to `options` first, and dates and times
+    /// are converted to the storage selected by `--in-date-storage`. Values that
+    /// cannot be parsed as the column type are kept as text.
+    pub fn parse_value(&self, source: &str, options: &DetectOptions) -> rusqlite::types::Value {
+        let text = || rusqlite::types::Value::Text(source.to_string());
         match self {
-            SqliteDataType::Integer => true,
-            SqliteDataType::Real => to != SqliteDataType::Integer,
-            SqliteDataType::Text => to == SqliteDataType::Text,
+            SqliteDataType::Integer => normalize_number(source, options)
+                .and_then(|number| i64::from_str(&number).ok())
+                .map_or_else(text, rusqlite::types::Value::Integer),
+            SqliteDataType::Real => normalize_number(source, options)
+                .and_then(|number| f64::from_str(&number).ok())
+                .map_or_else(text, rusqlite::types::Value::Real),
+            SqliteDataType::Text => text(),
+            SqliteDataType::Boolean => parse_boolean(source)
+                .map_or_else(text, |value| rusqlite::types::Value::Integer(value as i64)),
+            SqliteDataType::Date | SqliteDataType::Datetime | SqliteDataType::Time => {
+                TemporalValue::parse(source, &options.date_formats)
+                    .map(|value| match self {
+                        SqliteDataType::Datetime => value.into_datetime(),
+                        _ => value,
+                    })
+                    .map_or_else(text, |value| value.to_sql_value(options.date_storage))
+            }
         }
     }
 
@@ -40,25 +152,65 @@ impl SqliteDataType {
     ///
     /// # Arguments
     /// * `s` - Input string to analyze.
-    /// * `allow_leading_zeros` - Whether numbers with leading zeros are allowed.
+    /// * `options` - Accepted number and date formats.
+    ///
+    /// With `--in-detect-boolean`, `0` and `1` are detected as `Boolean`, and
+    /// widened to `Integer` when other numbers appear in the column.
+    ///
+    /// Dates, datetimes and times are detected with `--in-detect-dates` or
+    /// `--in-date-format`. Formats given with `--in-date-format` take precedence
+    /// over numbers, so formats such as `%Y%m%d` can be used.
     ///
     /// # Returns
     /// * `SqliteDataType` - The inferred data type.
-    pub fn detect_type(s: &str, allow_leading_zeros: bool) -> Self {
-        if is_valid_number(s, allow_leading_zeros) {
-            if i64::from_str(s).is_ok() {
-                Self::Integer
-            } else if f64::from_str(s).is_ok() {
-                Self::Real
-            } else {
-                Self::Text
-            }
-        } else {
-            Self::Text
+    pub fn detect_type(s: &str, options: &DetectOptions) -> Self {
+        if options.detect_boolean && parse_boolean(s).is_some() {
+            return Self::Boolean;
         }
+        let temporal = || {
+            options
+                .detect_dates
+                .then(|| TemporalValue::parse(s, &options.date_formats))
+                .flatten()
+                .map(|v| v.data_type())
+        };
+        if !options.date_formats.is_empty()
+            && let Some(data_type) = temporal()
+        {
+            return data_type;
+        }
+        match normalize_number(s, options) {
+            Some(number) if i64::from_str(&number).is_ok() => Self::Integer,
+            Some(number) if f64::from_str(&number).is_ok() => Self::Real,
+            _ => temporal().unwrap_or(Self::Text),
+        }
+    }
+}
+
+/// Parses a boolean value: `true`/`false`, `yes`/`no`, `y`/`n` in any case, or `1`/`0`.
+fn parse_boolean(source: &str) -> Option<bool> {
+    const TRUE_VALUES: [&str; 4] = ["true", "yes", "y", "1"];
+    const FALSE_VALUES: [&str; 4] = ["false", "no", "n", "0"];
+    if TRUE_VALUES.iter().any(|v| v.eq_ignore_ascii_case(source)) {
+        Some(true)
+    } else if FALSE_VALUES.iter().any(|v| v.eq_ignore_ascii_case(source)) {
+        Some(false)
+    } else {
+        None
     }
 }
 
+/// Returns the number in plain notation (e.g. `1234.5`), or `None` if `source` is not a number.
+///
+/// Grouping separators and the decimal mark are converted according to `--in-number-locale`.
+fn normalize_number<'a>(source: &'a str, options: &DetectOptions) -> Option<Cow<'a, str>> {
+    let number = match options.number_locale {
+        None => Cow::Borrowed(source),
+        Some(locale) => Cow::Owned(locale.normalize(source)?),
+    };
+    is_valid_number(&number, options.allow_leading_zeros).then_some(number)
+}
+
 /// Validates if the input string is a number based on the specified format.
 ///
 /// # Arguments
@@ -69,9 +221,9 @@ impl SqliteDataType {
 /// * `bool` - `true` if valid, otherwise `false`.
 fn is_valid_number(source: &str, allow_leading_zeros: bool) -> bool {
     let re = if allow_leading_zeros {
-        lazy_regex::regex!(r#"^-?((\d+)|(\d+\.\d*)|(\.\d+))$"#)
+        lazy_regex::regex!(r#"^-?((\d+)|(\d+\.\d*)|(\.\d+))([eE][+-]?\d+)?$"#)
     } else {
-        lazy_regex::regex!(r#"^-?(([1-9]\d*)|([1-9]\d*\.\d*)|(0?\.\d+)|0)$"#)
+        lazy_regex::regex!(r#"^-?(([1-9]\d*)|([1-9]\d*\.\d*)|(0?\.\d+)|0)([eE][+-]?\d+)?$"#)
     };
     re.is_match(source)
 }
@@ -80,39 +232,45 @@ fn is_valid_number(source: &str, allow_leading_zeros: bool) -> bool {
 fn test_is_valid_number() {
     let positive_cases = vec![
         "0", "-0", "0.0", "-0.0", ".0", "-.0", "0.10", "-0.10", ".120", "-.120", "1", "-1", "1.",
-        "-1.", "1.0", "-1.0", "10", "-10", "10.", "-10.", "10.0", "-10.0",
+        "-1.", "1.0", "-1.0", "10", "-10", "10.", "-10.", "10.0", "-10.0", "1e5", "1.2E-3",
+        "-.5e+10",
     ];
     let leading_zeros_cases = vec!["001", "-00"];
     let negative_cases = vec![
-        "1a", "2..1", "..2", "--0", // Contains space
+        "1a", "2..1", "..2", "--0", "1e", "e5", "1e+", "1e2.5", // Contains space
         " 10", "102 ", "4 5",
     ];
 
-    for allow_leading_zeros_for_number in [true, false] {
-        println!(
-            "allow_leading_zeros_for_number:{}",
-            allow_leading_zeros_for_number
-        );
-        for case in positive_cases.clone() {
-            println!(
-                "[positive]      {} - {}",
-                case,
-                is_valid_number(case, allow_leading_zeros_for_number)
-            );
+    for allow_leading_zeros in [true, false] {
+        for case in &positive_cases {
+            assert!(is_valid_number(case, allow_leading_zeros), "{}", case);
         }
-        for case in leading_zeros_cases.clone() {
-            println!(
-                "[leading_zeros] {} - {}",
-                case,
-                is_valid_number(case, allow_leading_zeros_for_number)
+        for case in &leading_zeros_cases {
+            assert_eq!(
+                is_valid_number(case, allow_leading_zeros),
+                allow_leading_zeros,
+                "{}",
+                case
             );
         }
-        for case in negative_cases.clone() {
-            println!(
-                "[negative]      {} - {}",
-                case,
-                is_valid_number(case, allow_leading_zeros_for_number)
-            );
+        for case in &negative_cases {
+            assert!(!is_valid_number(case, allow_leading_zeros), "{}", case);
         }
     }
 }
+
+#[test]
+fn test_from_declared_type() {
+    use SqliteDataType::*;
+    assert_eq!(SqliteDataType::from_declared_type("DATE"), Some(Date));
+    assert_eq!(SqliteDataType::from_declared_type("bigint"), Some(Integer));
+    assert_eq!(
+        SqliteDataType::from_declared_type("VARCHAR(20)"),
+        Some(Text)
+    );
+    assert_eq!(
+        SqliteDataType::from_declared_type("DOUBLE PRECISION"),
+        Some(Real)
+    );
+    assert_eq!(SqliteDataType::from_declared_type("NUMERIC"), None);
+}
@@ -0,0 +1,75 @@
+use crate::args_util::blob_format::BlobFormat;
+use std::fmt::{Display, Formatter};
+
+/// A single value returned by SQLite, keeping its storage class.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqliteValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Display for SqliteValue {
+    /// Converts `SqliteValue` to its text representation. BLOBs are written in hex.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_text(BlobFormat::Hex))
+    }
+}
+
+impl From<rusqlite::types::ValueRef<'_>> for SqliteValue {
+    /// Copies a value borrowed from a result row. Invalid UTF-8 in text is replaced.
+    fn from(value: rusqlite::types::ValueRef<'_>) -> Self {
+        match value {
+            rusqlite::types::ValueRef::Null => SqliteValue::Null,
+            rusqlite::types::ValueRef::Integer(value) => SqliteValue::Integer(value),
+            rusqlite::types::ValueRef::Real(value) => SqliteValue::Real(value),
+            rusqlite::types::ValueRef::Text(value) => {
+                SqliteValue::Text(String::from_utf8_lossy(value).to_string())
+            }
+            rusqlite::types::ValueRef::Blob(value) => SqliteValue::Blob(value.to_vec()),
+        }
+    }
+}
+
+impl SqliteValue {
+    /// Converts the value to text.
+    ///
+    /// - `NULL` becomes an empty string.
+    /// - `REAL` keeps a fractional part or exponent, so it reads back as the same number.
+    /// - `BLOB` is encoded with `blob_format`.
+    pub fn to_text(&self, blob_format: BlobFormat) -> String {
+        match self {
+            SqliteValue::Null => String::new(),
+            SqliteValue::Integer(value) => value.to_string(),
+            SqliteValue::Real(value) => format_real(*value),
+            SqliteValue::Text(value) => value.to_string(),
+            SqliteValue::Blob(value) => blob_format.encode(value),
+        }
+    }
+}
+
+/// Formats a REAL value with the shortest representation that round-trips.
+///
+/// Unlike `f64::to_string`, integral values keep `.0` and very large or small
+/// values use an exponent (e.g. `4.0`, `1e300`, `1e-7`).
+fn format_real(value: f64) -> String {
+    format!("{:?}", value)
+}
+
+#[test]
+fn test_to_text() {
+    assert_eq!(SqliteValue::Null.to_text(BlobFormat::Hex), "");
+    assert_eq!(SqliteValue::Real(4.0).to_text(BlobFormat::Hex), "4.0");
+    assert_eq!(SqliteValue::Real(0.1).to_text(BlobFormat::Hex), "0.1");
+    assert_eq!(SqliteValue::Real(1e300).to_text(BlobFormat::Hex), "1e300");
+    assert_eq!(
+        SqliteValue::Blob(vec![0x00, 0xff, 0x10]).to_text(BlobFormat::Hex),
+        "00ff10"
+    );
+    assert_eq!(
+        SqliteValue::Blob(b"csvs".to_vec()).to_text(BlobFormat::Base64),
+        "Y3N2cw=="
+    );
+}
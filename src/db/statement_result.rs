@@ -1,14 +1,25 @@
+use crate::db::sqlite_value::SqliteValue;
+
 /// Captures the result of an executed SQL statement.
-#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct StatementResult {
     /// Column names from the query result.
     pub header: Vec<String>,
 
-    /// Rows returned by the query, each row as a vector of strings.
-    pub rows: Vec<Vec<String>>,
+    /// Declared types of the columns, e.g. `DATE`; `None` for expressions.
+    pub declared_types: Vec<Option<String>>,
+
+    /// Rows returned by the query, each row as a vector of values.
+    pub rows: Vec<Vec<SqliteValue>>,
 
     /// Time taken to execute the SQL statement.
     pub elapsed: std::time::Duration,
+
+    /// `true` for the rows of a query or PRAGMA, `false` for the report of an executed statement.
+    pub is_query: bool,
+
+    /// File the result was written to by an output annotation.
+    pub output: Option<String>,
 }
 
 /// Paged representation of an SQL execution result.
@@ -0,0 +1,234 @@
+use crate::db::detect_options::DetectOptions;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::sqlite_quoted::SqliteQuoted;
+use crate::db::table_schema::TableSchema;
+use tracing::debug;
+
+/// Bulk loads rows into a newly created table.
+///
+/// Rows are bound to a cached prepared statement and inserted within a single
+/// transaction. Connection settings are tuned for the import and restored by
+/// `finish`; if the loader is dropped without `finish`, for example after a
+/// failed insert, the transaction is rolled back and the settings restored.
+pub struct TableLoader<'conn> {
+    /// Transaction holding the whole import.
+    tx: rusqlite::Transaction<'conn>,
+
+    /// Parameterized `INSERT` statement.
+    sql_insert: String,
+
+    /// Data types used to bind each column.
+    types: Vec<SqliteDataType>,
+
+    /// Settings used to convert values.
+    options: DetectOptions,
+
+    /// Values stored for empty cells per column.
+    defaults: Vec<Option<String>>,
+
+    /// Connection settings to restore after the import.
+    /// Declared after `tx`, so a dropped loader rolls back before restoring.
+    saved_pragmas: SavedPragmas<'conn>,
+}
+
+/// Connection settings changed during an import, restored when dropped.
+struct SavedPragmas<'conn> {
+    conn: &'conn rusqlite::Connection,
+    synchronous: i64,
+    cache_size: i64,
+    journal_mode: String,
+    restored: bool,
+}
+
+impl<'conn> TableLoader<'conn> {
+    /// Creates the table and starts the import transaction.
+    ///
+    /// # Arguments
+    /// * `conn` - Connection to import into.
+    /// * `table_name` - Name of the table to create.
+    /// * `raw_id` - Name of the auto-increment primary key column.
+    /// * `schema` - Column layout of the table.
+    pub fn new(
+        conn: &'conn rusqlite::Connection,
+        table_name: &str,
+        raw_id: &str,
+        schema: &TableSchema,
+    ) -> anyhow::Result<Self> {
+        let saved_pragmas = SavedPragmas::tune(conn)?;
+        let tx = conn.unchecked_transaction()?;
+
+        tx.execute(&schema.create_table_statement(table_name, raw_id), ())?;
+
+        let sql_insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            SqliteQuoted::Field(table_name.to_string()).get(),
+            schema.quoted_headers().join(", "),
+            vec!["?"; schema.headers.len()].join(", ")
+        );
+        debug!("Prepared insert statement: {}", sql_insert);
+
+        let types = (0..schema.headers.len())
+            .map(|index| schema.data_type(index))
+            .collect();
+        let defaults = (0..schema.headers.len())
+            .map(|index| schema.default_value(index).map(str::to_string))
+            .collect();
+
+        Ok(Self {
+            tx,
+            sql_insert,
+            types,
+            options: schema.options.clone(),
+            defaults,
+            saved_pragmas,
+        })
+    }
+
+    /// Inserts a single row.
+    ///
+    /// # Arguments
+    /// * `values` - One value per column; `None` is stored as the column default or `NULL`.
+    pub fn insert(&self, values: &[Option<&str>]) -> anyhow::Result<()> {
+        let mut stmt = self.tx.prepare_cached(&self.sql_insert)?;
+        let params = self.types.iter().enumerate().map(|(index, data_type)| {
+            let value = values.get(index).copied().flatten();
+            match value.or(self.defaults[index].as_deref()) {
+                None => rusqlite::types::Value::Null,
+                Some(value) => data_type.parse_value(value, &self.options),
+            }
+        });
+        stmt.execute(rusqlite::params_from_iter(params))?;
+        Ok(())
+    }
+
+    /// Commits the import and restores the connection settings.
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        self.tx.commit()?;
+        self.saved_pragmas.restore()
+    }
+}
+
+impl<'conn> SavedPragmas<'conn> {
+    /// Relaxes durability settings for a faster import.
+    ///
+    /// # Returns
+    /// The previous settings.
+    fn tune(conn: &'conn rusqlite::Connection) -> anyhow::Result<Self> {
+        let saved_pragmas = Self {
+            conn,
+            synchronous: conn.pragma_query_value(None, "synchronous", |row| row.get(0))?,
+            cache_size: conn.pragma_query_value(None, "cache_size", |row| row.get(0))?,
+            journal_mode: conn.pragma_query_value(None, "journal_mode", |row| row.get(0))?,
+            restored: false,
+        };
+
+        conn.pragma_update(None, "synchronous", "OFF")?;
+        conn.pragma_update(None, "cache_size", -65536)?; // 64 MiB
+        if !saved_pragmas.journal_mode.eq_ignore_ascii_case("wal") {
+            // WAL is persistent and already suited for bulk writes
+            conn.pragma_update_and_check(None, "journal_mode", "MEMORY", |row| {
+                row.get::<_, String>(0)
+            })?;
+        }
+        Ok(saved_pragmas)
+    }
+
+    /// Restores the settings changed by `tune`.
+    fn restore(&mut self) -> anyhow::Result<()> {
+        self.restored = true;
+        self.conn
+            .pragma_update(None, "synchronous", self.synchronous)?;
+        self.conn
+            .pragma_update(None, "cache_size", self.cache_size)?;
+        if !self.journal_mode.eq_ignore_ascii_case("wal") {
+            self.conn
+                .pragma_update_and_check(None, "journal_mode", &self.journal_mode, |row| {
+                    row.get::<_, String>(0)
+                })?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SavedPragmas<'_> {
+    fn drop(&mut self) {
+        if !self.restored
+            && let Err(err) = self.restore()
+        {
+            debug!("Failed to restore connection settings: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_loader(conn: &rusqlite::Connection) -> TableLoader<'_> {
+    let mut schema = TableSchema::new(DetectOptions::default(), std::collections::HashMap::new());
+    for name in ["i", "r", "t"] {
+        schema.column_index(name);
+    }
+    for (i, r, t) in [("1", "1.5", "00123"), ("2", "2", "x")] {
+        schema.observe(0, Some(i)).unwrap();
+        schema.observe(1, Some(r)).unwrap();
+        schema.observe(2, Some(t)).unwrap();
+        schema.end_row();
+    }
+    schema.finish("t").unwrap();
+    TableLoader::new(conn, "t", "raw_id", &schema).unwrap()
+}
+
+#[cfg(test)]
+fn pragmas(conn: &rusqlite::Connection) -> (i64, i64, String) {
+    (
+        conn.pragma_query_value(None, "synchronous", |row| row.get(0))
+            .unwrap(),
+        conn.pragma_query_value(None, "cache_size", |row| row.get(0))
+            .unwrap(),
+        conn.pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .unwrap(),
+    )
+}
+
+#[test]
+fn test_insert_typed() {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    let loader = test_loader(&conn);
+    loader
+        .insert(&[Some("1"), Some("1.5"), Some("00123")])
+        .unwrap();
+    loader.finish().unwrap();
+
+    let row: (String, String, String, String) = conn
+        .query_row(
+            "SELECT typeof(i), typeof(r), typeof(t), t FROM t",
+            (),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .unwrap();
+    assert_eq!(
+        row,
+        (
+            "integer".to_string(),
+            "real".to_string(),
+            "text".to_string(),
+            "00123".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_failed_insert_restores_pragmas() {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    let before = pragmas(&conn);
+    {
+        let loader = test_loader(&conn);
+        assert_ne!(pragmas(&conn), before);
+        // The columns never had empty values, so they are NOT NULL
+        assert!(loader.insert(&[None, None, None]).is_err());
+    }
+    assert_eq!(pragmas(&conn), before);
+    assert!(conn.is_autocommit());
+    assert!(
+        conn.prepare("SELECT * FROM t").is_err(),
+        "the import should be rolled back"
+    );
+}
@@ -0,0 +1,82 @@
+use crate::args_util::compression::strip_compression_extension;
+use crate::args_util::input_file::InputFile;
+use crate::args_util::table_name_style::TableNameStyle;
+use crate::db::is_reserved_table_name::is_reserved_table_name;
+use anyhow::Context;
+
+/// Derives a table name from the path of an input file.
+///
+/// A compression extension is not part of the name, so `events.csv.gz` is imported as `events.csv`.
+///
+/// # Arguments
+/// * `file_path` - Path of the input file.
+/// * `style` - Part of the path used as the table name.
+///
+/// # Returns
+/// The table name, or an error if it cannot be used as a table name.
+pub fn table_name_from_path(file_path: &str, style: TableNameStyle) -> anyhow::Result<String> {
+    let path = std::path::Path::new(strip_compression_extension(file_path));
+    let table_name = match style {
+        TableNameStyle::Stem => path.file_stem(),
+        TableNameStyle::Basename => path.file_name(),
+        TableNameStyle::Path => Some(path.as_os_str()),
+    }
+    .context(format!("{} not found", file_path))?
+    .to_str()
+    .context("Cannot convert OsStr to Str")?;
+
+    let table_name = match style {
+        TableNameStyle::Path => table_name
+            .strip_prefix("./")
+            .unwrap_or(table_name)
+            .replace('\\', "/"),
+        _ => table_name.to_string(),
+    };
+    validate_table_name(&table_name)?;
+    Ok(table_name)
+}
+
+/// Derives a table name for an input file, with the sheet name for a sheet of a workbook.
+///
+/// Sheets are named like `report.xlsx#Sheet1`, with the workbook part following `style`.
+pub fn table_name_from_input(
+    input_file: &InputFile,
+    style: TableNameStyle,
+) -> anyhow::Result<String> {
+    match input_file.sheet() {
+        Some(sheet) => Ok(format!(
+            "{}#{}",
+            table_name_from_path(&input_file.path, style)?,
+            sheet
+        )),
+        None => table_name_from_path(input_file.inner_name(), style),
+    }
+}
+
+/// Checks that a table name given for an input can be used.
+pub fn validate_table_name(table_name: &str) -> anyhow::Result<()> {
+    if table_name.is_empty() {
+        return Err(anyhow::anyhow!("Table name must not be empty"));
+    }
+    if is_reserved_table_name(table_name) {
+        return Err(anyhow::anyhow!(
+            "You cannot use the SQLite's reserved word 'sqlite_' as a table name: {}",
+            table_name
+        ));
+    };
+    Ok(())
+}
+
+#[test]
+fn test_table_name_from_path() {
+    let file_path = "./data/2024/sales.csv";
+    let name = |style| table_name_from_path(file_path, style).unwrap();
+    assert_eq!(name(TableNameStyle::Stem), "sales");
+    assert_eq!(name(TableNameStyle::Basename), "sales.csv");
+    assert_eq!(name(TableNameStyle::Path), "data/2024/sales.csv");
+    assert_eq!(
+        table_name_from_path("events.csv.gz", TableNameStyle::Basename).unwrap(),
+        "events.csv"
+    );
+    assert!(table_name_from_path("sqlite_stat1.csv", TableNameStyle::Basename).is_err());
+}
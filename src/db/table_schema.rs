@@ -0,0 +1,443 @@
+use crate::db::detect_options::DetectOptions;
+use crate::db::schema_overrides::ColumnOverride;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::sqlite_quoted::SqliteQuoted;
+use anyhow::Context;
+use std::collections::{HashMap, HashSet};
+
+/// Column layout of a table inferred while scanning input data.
+///
+/// Values are observed one by one, so the schema can be built in a first pass
+/// without keeping any rows in memory. Columns are identified by name, so
+/// several inputs with different column orders can share a schema. Columns
+/// with an override from `--in-schema` or `--in-type` keep the given definition
+/// instead.
+#[derive(Default, Debug, Clone)]
+pub struct TableSchema {
+    /// Column names in order of appearance. Add columns with `column_index`.
+    pub headers: Vec<String>,
+
+    /// Column index by name.
+    indexes: HashMap<String, usize>,
+
+    /// Number of rows containing each column, even if empty.
+    observed_counts: Vec<usize>,
+
+    /// Number of rows observed.
+    row_count: usize,
+
+    /// Widest data type detected per column index.
+    types: HashMap<usize, SqliteDataType>,
+
+    /// Column indexes that contained at least one empty value.
+    nullable: HashMap<usize, bool>,
+
+    /// Column indexes that contained boolean words such as `yes`, which are not numbers.
+    boolean_words: HashSet<usize>,
+
+    /// Settings used to detect types and convert values.
+    pub options: DetectOptions,
+
+    /// Column definitions replacing inference, keyed by column name in the input.
+    overrides: HashMap<String, ColumnOverride>,
+}
+
+impl TableSchema {
+    /// Creates an empty schema.
+    ///
+    /// # Arguments
+    /// * `options` - Settings used to detect types and convert values.
+    /// * `overrides` - Column definitions replacing inference.
+    pub fn new(options: DetectOptions, overrides: HashMap<String, ColumnOverride>) -> Self {
+        Self {
+            options,
+            overrides,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the index of a column, adding the column if it is new.
+    pub fn column_index(&mut self, name: &str) -> usize {
+        if let Some(index) = self.indexes.get(name) {
+            return *index;
+        }
+        self.headers.push(name.to_string());
+        self.observed_counts.push(0);
+        self.indexes
+            .insert(name.to_string(), self.headers.len() - 1);
+        self.headers.len() - 1
+    }
+
+    /// Returns the index of an existing column.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.indexes.get(name).copied()
+    }
+
+    /// Adds a column filled by csvs rather than the input, such as `_source_file`.
+    ///
+    /// # Returns
+    /// The column index, or an error if an input already has a column with the same name.
+    pub fn add_generated_column(
+        &mut self,
+        name: &str,
+        data_type: SqliteDataType,
+    ) -> anyhow::Result<usize> {
+        if self.indexes.contains_key(name) {
+            return Err(anyhow::anyhow!(
+                "Column \"{}\" already exists in the input",
+                name
+            ));
+        }
+        self.overrides.insert(
+            name.to_string(),
+            ColumnOverride {
+                data_type: Some(data_type),
+                not_null: Some(true),
+                ..Default::default()
+            },
+        );
+        Ok(self.column_index(name))
+    }
+
+    /// Counts a row after its values are observed.
+    ///
+    /// Columns that are missing from some rows become nullable in `finish`.
+    pub fn end_row(&mut self) {
+        self.row_count += 1;
+    }
+
+    /// Updates the column type and nullability with a single value.
+    ///
+    /// # Arguments
+    /// * `index` - Column index of the value.
+    /// * `value` - The value, or `None` when the cell is empty.
+    ///
+    /// # Returns
+    /// An error if the value does not fit the type or nullability given for the column.
+    pub fn observe(&mut self, index: usize, value: Option<&str>) -> anyhow::Result<()> {
+        self.observed_counts[index] += 1;
+        self.observe_value(index, value, None)
+    }
+
+    /// Updates the column type and nullability with a value whose type is known
+    /// from the input, such as a date cell of a spreadsheet.
+    ///
+    /// The type is merged with the other values of the column instead of being
+    /// detected from the text, so `value` must be in a form `data_type` can parse.
+    pub fn observe_typed(
+        &mut self,
+        index: usize,
+        value: &str,
+        data_type: SqliteDataType,
+    ) -> anyhow::Result<()> {
+        self.observed_counts[index] += 1;
+        self.observe_value(index, Some(value), Some(data_type))
+    }
+
+    /// Updates the column type and nullability without counting the value.
+    ///
+    /// The type of the value is detected unless `known_type` is given.
+    fn observe_value(
+        &mut self,
+        index: usize,
+        value: Option<&str>,
+        known_type: Option<SqliteDataType>,
+    ) -> anyhow::Result<()> {
+        let column_override = self.column_override(index);
+        match value {
+            Some(value) => {
+                if let Some(data_type) = column_override.and_then(|o| o.data_type) {
+                    if !data_type.accepts(value, &self.options) {
+                        return Err(anyhow::anyhow!(
+                            "Value '{}' in column \"{}\" does not fit {}",
+                            value,
+                            self.headers[index],
+                            data_type
+                        ));
+                    }
+                    return Ok(());
+                }
+
+                // Integers of the input may exceed the range of INTEGER, e.g. UInt64 in Parquet
+                let detected = known_type
+                    .filter(|data_type| {
+                        *data_type != SqliteDataType::Integer
+                            || data_type.accepts(value, &self.options)
+                    })
+                    .unwrap_or_else(|| SqliteDataType::detect_type(value, &self.options));
+                if detected == SqliteDataType::Boolean && value != "0" && value != "1" {
+                    self.boolean_words.insert(index);
+                }
+                let mut merged = match self.types.get(&index) {
+                    Some(existing) => existing.merge(detected),
+                    None => detected,
+                };
+                let is_number = matches!(merged, SqliteDataType::Integer | SqliteDataType::Real);
+                if is_number && self.boolean_words.contains(&index) {
+                    merged = SqliteDataType::Text;
+                }
+                self.types.insert(index, merged);
+            }
+            None => {
+                if column_override.is_some_and(|o| o.not_null == Some(true) && o.default.is_none())
+                {
+                    return Err(anyhow::anyhow!(
+                        "Column \"{}\" is NOT NULL, but a value is empty",
+                        self.headers[index]
+                    ));
+                }
+                self.nullable.entry(index).or_insert(true);
+            }
+        }
+        Ok(())
+    }
+
+    /// Completes the schema after all rows are observed.
+    ///
+    /// Columns missing from some rows become nullable, and the overrides are checked.
+    ///
+    /// # Arguments
+    /// * `table_name` - Table name used in error messages.
+    pub fn finish(&mut self, table_name: &str) -> anyhow::Result<()> {
+        for index in 0..self.headers.len() {
+            if self.observed_counts[index] < self.row_count {
+                self.observe_value(index, None, None)
+                    .with_context(|| format!("Column missing from some rows of {}", table_name))?;
+            }
+        }
+        self.check_overrides(table_name)
+    }
+
+    /// Checks that every overridden column exists and its default fits its type.
+    fn check_overrides(&self, table_name: &str) -> anyhow::Result<()> {
+        for (column_name, column_override) in &self.overrides {
+            let index = self.position(column_name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Column \"{}\" given in the schema of {} not found",
+                    column_name,
+                    table_name
+                )
+            })?;
+            if let Some(default) = &column_override.default {
+                let data_type = self.data_type(index);
+                if !data_type.accepts(default, &self.options) {
+                    return Err(anyhow::anyhow!(
+                        "Default '{}' of column \"{}\" in {} does not fit {}",
+                        default,
+                        column_name,
+                        table_name,
+                        data_type
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the override of a column, if any.
+    fn column_override(&self, index: usize) -> Option<&ColumnOverride> {
+        if self.overrides.is_empty() {
+            return None;
+        }
+        self.overrides.get(&self.headers[index])
+    }
+
+    /// Returns the data type of a column: the given one, the detected one, or `Text`.
+    pub fn data_type(&self, index: usize) -> SqliteDataType {
+        self.column_override(index)
+            .and_then(|o| o.data_type)
+            .or_else(|| self.types.get(&index).copied())
+            .unwrap_or(SqliteDataType::Text)
+    }
+
+    /// Returns `true` if the column allows `NULL`.
+    ///
+    /// Columns allow `NULL` if they contained empty values without a default,
+    /// unless `not_null` is given for the column.
+    pub fn is_nullable(&self, index: usize) -> bool {
+        let column_override = self.column_override(index);
+        match column_override.and_then(|o| o.not_null) {
+            Some(not_null) => !not_null,
+            None => {
+                self.nullable.contains_key(&index)
+                    && column_override.is_none_or(|o| o.default.is_none())
+            }
+        }
+    }
+
+    /// Returns the value stored for empty cells of a column, if any.
+    pub fn default_value(&self, index: usize) -> Option<&str> {
+        self.column_override(index)
+            .and_then(|o| o.default.as_deref())
+    }
+
+    /// Returns the SQL-quoted column names, renamed as given in the overrides.
+    pub fn quoted_headers(&self) -> Vec<String> {
+        (0..self.headers.len())
+            .map(|index| {
+                let name = self
+                    .column_override(index)
+                    .and_then(|o| o.rename.as_deref())
+                    .unwrap_or(&self.headers[index]);
+                SqliteQuoted::Field(name.to_string()).get()
+            })
+            .collect()
+    }
+
+    /// Builds the `CREATE TABLE` statement for this schema.
+    ///
+    /// # Arguments
+    /// * `table_name` - Name of the table to create.
+    /// * `raw_id` - Name of the auto-increment primary key column.
+    pub fn create_table_statement(&self, table_name: &str, raw_id: &str) -> String {
+        let columns: Vec<String> = self
+            .quoted_headers()
+            .into_iter()
+            .enumerate()
+            .map(|(index, header_quoted)| {
+                let not_null = if self.is_nullable(index) {
+                    ""
+                } else {
+                    "NOT NULL"
+                };
+                let data_type = self.data_type(index);
+                let check = if data_type == SqliteDataType::Boolean {
+                    format!(" CHECK ({} IN (0, 1))", header_quoted)
+                } else {
+                    "".to_string()
+                };
+                let default = match self.default_value(index) {
+                    Some(default) => {
+                        format!(" DEFAULT {}", SqliteQuoted::Text(default.to_string()).get())
+                    }
+                    None => "".to_string(),
+                };
+                format!(
+                    "{} {} {}{}{}",
+                    header_quoted, data_type, not_null, default, check
+                )
+            })
+            .collect();
+
+        format!(
+            "CREATE TABLE {} ({} INTEGER PRIMARY KEY AUTOINCREMENT,{});",
+            SqliteQuoted::Field(table_name.to_string()).get(),
+            SqliteQuoted::Field(raw_id.to_string()).get(),
+            columns.join(",")
+        )
+    }
+}
+
+#[test]
+fn test_observe_boolean() {
+    let options = DetectOptions {
+        detect_boolean: true,
+        ..Default::default()
+    };
+    let mut schema = TableSchema::new(options, HashMap::new());
+    for name in ["a", "b", "c"] {
+        schema.column_index(name);
+    }
+    for (a, b, c) in [("yes", "1", "y"), ("No", "0", "0"), ("TRUE", "2", "7")] {
+        schema.observe(0, Some(a)).unwrap();
+        schema.observe(1, Some(b)).unwrap();
+        schema.observe(2, Some(c)).unwrap();
+    }
+    assert_eq!(schema.data_type(0), SqliteDataType::Boolean);
+    assert_eq!(schema.data_type(1), SqliteDataType::Integer);
+    assert_eq!(schema.data_type(2), SqliteDataType::Text);
+
+    // Booleans are detected only on request
+    let mut schema = TableSchema::new(DetectOptions::default(), HashMap::new());
+    for name in ["a", "b"] {
+        schema.column_index(name);
+    }
+    for (a, b) in [("yes", "1"), ("No", "0")] {
+        schema.observe(0, Some(a)).unwrap();
+        schema.observe(1, Some(b)).unwrap();
+    }
+    assert_eq!(schema.data_type(0), SqliteDataType::Text);
+    assert_eq!(schema.data_type(1), SqliteDataType::Integer);
+}
+
+#[test]
+fn test_observe_dates() {
+    for (detect_dates, expected) in [(false, SqliteDataType::Text), (true, SqliteDataType::Date)] {
+        let options = DetectOptions {
+            detect_dates,
+            ..Default::default()
+        };
+        let mut schema = TableSchema::new(options, HashMap::new());
+        schema.column_index("a");
+        schema.observe(0, Some("31.01.2024")).unwrap();
+        schema.observe(0, Some("2024-02-01")).unwrap();
+        assert_eq!(schema.data_type(0), expected);
+    }
+}
+
+#[test]
+fn test_observe_out_of_range_integer() {
+    let mut schema = TableSchema::new(DetectOptions::default(), HashMap::new());
+    schema.column_index("a");
+    schema
+        .observe_typed(0, "1", SqliteDataType::Integer)
+        .unwrap();
+    schema
+        .observe_typed(0, "18446744073709551615", SqliteDataType::Integer)
+        .unwrap();
+    assert_eq!(schema.data_type(0), SqliteDataType::Real);
+}
+
+#[test]
+fn test_observe_override() {
+    let overrides = HashMap::from([
+        (
+            "zip".to_string(),
+            ColumnOverride {
+                data_type: Some(SqliteDataType::Text),
+                rename: Some("postal_code".to_string()),
+                ..Default::default()
+            },
+        ),
+        (
+            "amount".to_string(),
+            ColumnOverride {
+                data_type: Some(SqliteDataType::Integer),
+                not_null: Some(true),
+                ..Default::default()
+            },
+        ),
+    ]);
+    let mut schema = TableSchema::new(DetectOptions::default(), overrides);
+    assert_eq!(schema.column_index("zip"), 0);
+    assert_eq!(schema.column_index("amount"), 1);
+    assert_eq!(schema.column_index("zip"), 0);
+
+    schema.observe(0, Some("01234")).unwrap();
+    schema.observe(1, Some("10")).unwrap();
+    assert_eq!(schema.data_type(0), SqliteDataType::Text);
+    assert_eq!(
+        schema.quoted_headers(),
+        vec!["\"postal_code\"", "\"amount\""]
+    );
+    assert!(schema.observe(1, Some("1.5")).is_err());
+    assert!(schema.observe(1, None).is_err());
+    schema.end_row();
+    assert!(schema.finish("t").is_ok());
+}
+
+#[test]
+fn test_finish_missing_column() {
+    let mut schema = TableSchema::new(DetectOptions::default(), HashMap::new());
+    let a = schema.column_index("a");
+    schema.observe(a, Some("1")).unwrap();
+    schema.end_row();
+    let b = schema.column_index("b");
+    schema.observe(a, Some("2")).unwrap();
+    schema.observe(b, Some("x")).unwrap();
+    schema.end_row();
+    schema.finish("t").unwrap();
+
+    assert!(!schema.is_nullable(a));
+    assert!(schema.is_nullable(b));
+}
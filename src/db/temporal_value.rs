@@ -0,0 +1,232 @@
+use crate::args_util::date_storage::DateStorage;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::sqlite_value::SqliteValue;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+
+/// A date, datetime or time of day recognized in an input value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemporalValue {
+    Date(NaiveDate),
+    /// Converted to UTC if the input had an offset.
+    Datetime(NaiveDateTime),
+    Time(NaiveTime),
+}
+
+impl TemporalValue {
+    /// Parses a date, datetime or time.
+    ///
+    /// # Arguments
+    /// * `source` - Input value.
+    /// * `formats` - `strftime`-style formats given with `--in-date-format`.
+    ///   Replaces the built-in formats when not empty.
+    pub fn parse(source: &str, formats: &[String]) -> Option<Self> {
+        if formats.is_empty() {
+            parse_builtin(source)
+        } else {
+            formats
+                .iter()
+                .find_map(|format| parse_with_format(source, format))
+        }
+    }
+
+    /// Reads a value of a `DATE`, `DATETIME` or `TIME` column, stored as ISO 8601
+    /// text or, with `--in-date-storage epoch`, as Unix time.
+    ///
+    /// Unix times at midnight are read as dates.
+    pub fn from_stored(value: &SqliteValue) -> Option<Self> {
+        let seconds = match value {
+            SqliteValue::Text(text) => return Self::parse(text, &[]),
+            SqliteValue::Integer(number) => *number as f64,
+            SqliteValue::Real(number) => *number,
+            _ => return None,
+        };
+        let datetime = DateTime::from_timestamp_micros((seconds * 1e6).round() as i64)?.naive_utc();
+        Some(if datetime.time() == NaiveTime::MIN {
+            TemporalValue::Date(datetime.date())
+        } else {
+            TemporalValue::Datetime(datetime)
+        })
+    }
+
+    /// Returns the logical column type of the value.
+    pub fn data_type(&self) -> SqliteDataType {
+        match self {
+            TemporalValue::Date(_) => SqliteDataType::Date,
+            TemporalValue::Datetime(_) => SqliteDataType::Datetime,
+            TemporalValue::Time(_) => SqliteDataType::Time,
+        }
+    }
+
+    /// Converts a date into a datetime at midnight, so dates fit a `DATETIME` column.
+    pub fn into_datetime(self) -> Self {
+        match self {
+            TemporalValue::Date(date) => TemporalValue::Datetime(date.and_time(NaiveTime::MIN)),
+            other => other,
+        }
+    }
+
+    /// Converts the value into its stored representation.
+    ///
+    /// Times are always stored as `HH:MM:SS` text.
+    pub fn to_sql_value(self, storage: DateStorage) -> rusqlite::types::Value {
+        match (self, storage) {
+            (TemporalValue::Date(date), DateStorage::Iso) => {
+                rusqlite::types::Value::Text(date.format("%Y-%m-%d").to_string())
+            }
+            (TemporalValue::Date(date), DateStorage::Epoch) => {
+                rusqlite::types::Value::Integer(date.and_time(NaiveTime::MIN).and_utc().timestamp())
+            }
+            (TemporalValue::Datetime(datetime), DateStorage::Iso) => {
+                rusqlite::types::Value::Text(datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+            }
+            (TemporalValue::Datetime(datetime), DateStorage::Epoch) => {
+                let datetime = datetime.and_utc();
+                match datetime.timestamp_subsec_nanos() {
+                    0 => rusqlite::types::Value::Integer(datetime.timestamp()),
+                    nanos => rusqlite::types::Value::Real(
+                        datetime.timestamp() as f64 + nanos as f64 / 1e9,
+                    ),
+                }
+            }
+            (TemporalValue::Time(time), _) => {
+                rusqlite::types::Value::Text(time.format("%H:%M:%S%.f").to_string())
+            }
+        }
+    }
+}
+
+/// Parses the built-in formats.
+///
+/// - ISO-8601 dates and datetimes, with `T` or a space, optional seconds, fraction and offset
+/// - `YYYY/MM/DD` and `DD.MM.YYYY`, optionally followed by a time
+/// - RFC 2822, e.g. `Tue, 1 Jul 2003 10:52:37 +0200`
+/// - Times of day, `HH:MM` or `HH:MM:SS`
+fn parse_builtin(source: &str) -> Option<TemporalValue> {
+    // Skip the patterns for most text cheaply
+    let bytes = source.as_bytes();
+    let starts_with_digit = bytes.first().is_some_and(u8::is_ascii_digit);
+    let starts_with_weekday = bytes.get(3) == Some(&b',');
+    if !starts_with_digit && !starts_with_weekday {
+        return None;
+    }
+
+    if lazy_regex::regex_is_match!(r"^\d{2}:\d{2}(:\d{2}(\.\d+)?)?$", source) {
+        return parse_time(source).map(TemporalValue::Time);
+    }
+
+    if let Some((_, date, time, offset)) = lazy_regex::regex_captures!(
+        r"^(\d{4}-\d{2}-\d{2}|\d{4}/\d{2}/\d{2}|\d{2}\.\d{2}\.\d{4})(?:[T ](\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?)(Z|[+-]\d{2}:?\d{2})?)?$",
+        source
+    ) {
+        let date_format = match date.as_bytes()[2] {
+            b'.' => "%d.%m.%Y",
+            _ if date.contains('/') => "%Y/%m/%d",
+            _ => "%Y-%m-%d",
+        };
+        let date = NaiveDate::parse_from_str(date, date_format).ok()?;
+        if time.is_empty() {
+            return Some(TemporalValue::Date(date));
+        }
+        let datetime = date.and_time(parse_time(time)?);
+        return Some(TemporalValue::Datetime(datetime - parse_offset(offset)?));
+    }
+
+    if lazy_regex::regex_is_match!(
+        r"^([A-Za-z]{3}, )?\d{1,2} [A-Za-z]{3} \d{4} \d{2}:\d{2}",
+        source
+    ) {
+        return DateTime::parse_from_rfc2822(source)
+            .ok()
+            .map(|datetime| TemporalValue::Datetime(datetime.naive_utc()));
+    }
+
+    None
+}
+
+/// Parses `source` with a `strftime`-style format, trying the most specific value first.
+fn parse_with_format(source: &str, format: &str) -> Option<TemporalValue> {
+    if let Ok(datetime) = DateTime::parse_from_str(source, format) {
+        Some(TemporalValue::Datetime(datetime.naive_utc()))
+    } else if let Ok(datetime) = NaiveDateTime::parse_from_str(source, format) {
+        Some(TemporalValue::Datetime(datetime))
+    } else if let Ok(date) = NaiveDate::parse_from_str(source, format) {
+        Some(TemporalValue::Date(date))
+    } else {
+        NaiveTime::parse_from_str(source, format)
+            .ok()
+            .map(TemporalValue::Time)
+    }
+}
+
+/// Parses `HH:MM` or `HH:MM:SS` with an optional fraction.
+fn parse_time(source: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(source, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(source, "%H:%M"))
+        .ok()
+}
+
+/// Parses an offset from UTC such as `Z`, `+09:00` or `-0500`. Empty means UTC.
+fn parse_offset(source: &str) -> Option<TimeDelta> {
+    if source.is_empty() || source == "Z" {
+        return Some(TimeDelta::zero());
+    }
+    let digits = source[1..].replace(':', "");
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    let offset = TimeDelta::minutes(hours * 60 + minutes);
+    Some(if source.starts_with('-') {
+        -offset
+    } else {
+        offset
+    })
+}
+
+#[test]
+fn test_parse() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    let datetime = |y, m, d, h, mi, s| date(y, m, d).and_hms_opt(h, mi, s).unwrap();
+
+    assert_eq!(
+        TemporalValue::parse("2024-01-31", &[]),
+        Some(TemporalValue::Date(date(2024, 1, 31)))
+    );
+    assert_eq!(
+        TemporalValue::parse("2024/01/31", &[]),
+        Some(TemporalValue::Date(date(2024, 1, 31)))
+    );
+    assert_eq!(
+        TemporalValue::parse("31.01.2024", &[]),
+        Some(TemporalValue::Date(date(2024, 1, 31)))
+    );
+    assert_eq!(
+        TemporalValue::parse("2024-01-31T13:45:00+09:00", &[]),
+        Some(TemporalValue::Datetime(datetime(2024, 1, 31, 4, 45, 0)))
+    );
+    assert_eq!(
+        TemporalValue::parse("2024-01-31 13:45", &[]),
+        Some(TemporalValue::Datetime(datetime(2024, 1, 31, 13, 45, 0)))
+    );
+    assert_eq!(
+        TemporalValue::parse("Tue, 1 Jul 2003 10:52:37 +0200", &[]),
+        Some(TemporalValue::Datetime(datetime(2003, 7, 1, 8, 52, 37)))
+    );
+    assert_eq!(
+        TemporalValue::parse("13:45:30", &[]),
+        Some(TemporalValue::Time(
+            NaiveTime::from_hms_opt(13, 45, 30).unwrap()
+        ))
+    );
+    assert_eq!(TemporalValue::parse("2024-02-30", &[]), None);
+    assert_eq!(TemporalValue::parse("20240131", &[]), None);
+    assert_eq!(TemporalValue::parse("01/31/2024", &[]), None);
+
+    let formats = vec!["%m/%d/%Y".to_string()];
+    assert_eq!(
+        TemporalValue::parse("01/31/2024", &formats),
+        Some(TemporalValue::Date(date(2024, 1, 31)))
+    );
+    assert_eq!(TemporalValue::parse("2024-01-31", &formats), None);
+}
@@ -0,0 +1,132 @@
+use crate::args_util::compression::Compression;
+use crate::args_util::input_file::InputFile;
+use anyhow::Context;
+use std::io::Read;
+
+/// Opens an input for one pass and calls `f` with its content.
+///
+/// Compressed content is decompressed, and archive members are extracted on the
+/// fly, so nothing is written to disk. The progress bar advances with the bytes
+/// read from the file, or from the archive member.
+///
+/// # Arguments
+/// * `input_file` - The input to read.
+/// * `pb` - Progress bar sized with `input_size`.
+/// * `f` - Reads the content.
+pub fn with_input_stream<T>(
+    input_file: &InputFile,
+    pb: &indicatif::ProgressBar,
+    f: impl FnOnce(&mut dyn Read) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    match &input_file.member {
+        None => {
+            let compression = Compression::detect_file(&input_file.path)
+                .with_context(|| format!("Failed to open {}", input_file.path))?;
+            let file = std::fs::File::open(&input_file.path)
+                .with_context(|| format!("Failed to open {}", input_file.path))?;
+            let mut reader = Compression::decoder(compression, pb.wrap_read(file))?;
+            f(&mut reader)
+        }
+        Some(member) => {
+            let mut archive = open_archive(&input_file.path)?;
+            let compression = Compression::detect(open_member(&mut archive, input_file, member)?)?
+                .or_else(|| Compression::from_filename(member));
+            let member_file = open_member(&mut archive, input_file, member)?;
+            let mut reader = Compression::decoder(compression, pb.wrap_read(member_file))?;
+            f(&mut reader)
+        }
+    }
+}
+
+/// Opens an input for random access, as needed by formats such as Parquet.
+///
+/// Compressed content and archive members are extracted to an anonymous
+/// temporary file first, which is removed when it is closed.
+pub fn open_seekable_input(input_file: &InputFile) -> anyhow::Result<std::fs::File> {
+    let compression = match &input_file.member {
+        None => Compression::detect_file(&input_file.path)
+            .with_context(|| format!("Failed to open {}", input_file.path))?,
+        Some(_) => None,
+    };
+    if input_file.member.is_none() && compression.is_none() {
+        return std::fs::File::open(&input_file.path)
+            .with_context(|| format!("Failed to open {}", input_file.path));
+    }
+
+    let mut spool = tempfile::tempfile().context("Failed to create a temporary file")?;
+    with_input_stream(input_file, &indicatif::ProgressBar::hidden(), |reader| {
+        std::io::copy(reader, &mut spool)
+            .with_context(|| format!("Failed to extract {}", input_file))?;
+        Ok(())
+    })?;
+    std::io::Seek::rewind(&mut spool)?;
+    Ok(spool)
+}
+
+/// Copies STDIN to a temporary file, so that it can be read twice
+/// without keeping its content in memory.
+///
+/// # Returns
+/// The temporary file, or `None` if STDIN is interactive or empty.
+pub fn spool_stdin() -> anyhow::Result<Option<tempfile::NamedTempFile>> {
+    let mut spool = tempfile::NamedTempFile::new().context("Failed to create a temporary file")?;
+    {
+        let mut stdin = std::io::stdin().lock();
+        if std::io::IsTerminal::is_terminal(&stdin) {
+            // If stdin is interactive, there is nothing to import
+            return Ok(None);
+        }
+        std::io::copy(&mut stdin, &mut spool).context("Failed to read from stdin")?;
+    }
+    if spool.as_file().metadata()?.len() == 0 {
+        return Ok(None);
+    }
+    Ok(Some(spool))
+}
+
+/// Detects the character encoding of the input from its first bytes after decompression.
+///
+/// # Returns
+/// The name of the encoding, e.g. `UTF-8` or `Shift_JIS`.
+pub fn detect_input_encoding(input_file: &InputFile) -> anyhow::Result<String> {
+    with_input_stream(input_file, &indicatif::ProgressBar::hidden(), |reader| {
+        let mut buf = [0; 1024];
+        let _ = std::io::BufReader::new(reader).read(&mut buf)?;
+        let mut detector = chardetng::EncodingDetector::new();
+
+        detector.feed(&buf, false);
+        Ok(detector.guess(None, true).name().to_string())
+    })
+}
+
+/// Returns the size in bytes of the input as read by `with_input_stream`.
+pub fn input_size(input_file: &InputFile) -> anyhow::Result<u64> {
+    match &input_file.member {
+        None => Ok(std::fs::metadata(&input_file.path)
+            .with_context(|| format!("Failed to open {}", input_file.path))?
+            .len()),
+        Some(member) => {
+            let mut archive = open_archive(&input_file.path)?;
+            Ok(open_member(&mut archive, input_file, member)?.size())
+        }
+    }
+}
+
+/// Opens a ZIP archive.
+fn open_archive(archive_path: &str) -> anyhow::Result<zip::ZipArchive<std::fs::File>> {
+    let file = std::fs::File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path))?;
+    zip::ZipArchive::new(file)
+        .with_context(|| format!("Failed to read ZIP archive {}", archive_path))
+}
+
+/// Opens a member of a ZIP archive for reading.
+fn open_member<'a>(
+    archive: &'a mut zip::ZipArchive<std::fs::File>,
+    input_file: &InputFile,
+    member: &str,
+) -> anyhow::Result<zip::read::ZipFile<'a>> {
+    archive
+        .by_name(member)
+        .with_context(|| format!("Failed to read {}", input_file))
+}
@@ -0,0 +1,2 @@
+pub mod json_parser;
+pub mod json_writer;
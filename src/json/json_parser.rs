@@ -0,0 +1,309 @@
+use crate::args_util::args::Args;
+use crate::args_util::input_file::InputFile;
+use crate::db::detect_options::DetectOptions;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::table_loader::TableLoader;
+use crate::db::table_schema::TableSchema;
+use crate::format::STYLE_BAR;
+use crate::input::input_stream::{input_size, with_input_stream};
+use anyhow::Context;
+use std::collections::HashSet;
+use std::io::BufRead;
+use tracing::debug;
+
+/// Parses JSON or NDJSON files and imports them into the SQLite database.
+///
+/// Each object becomes a row. Nested objects are flattened into dotted column
+/// names (e.g. `address.city`), and arrays and empty objects are stored as JSON
+/// text usable with SQLite's `json_extract`. Strings are imported as text, and
+/// strings given with `--in-null` as `NULL`.
+#[derive(Debug)]
+pub struct JsonParser<'a> {
+    /// Reference to application arguments
+    args: &'a Args,
+}
+
+/// Flattened object: column names with their values; `None` is `NULL`.
+type FlatRecord = Vec<(String, Option<FlatValue>)>;
+
+/// Value of a flattened member, with its type if known from the JSON type:
+/// strings and JSON text are `TEXT`, while the type of numbers and booleans is detected.
+type FlatValue = (String, Option<SqliteDataType>);
+
+impl<'a> JsonParser<'a> {
+    /// Creates a new `JsonParser` instance
+    pub fn new(args: &'a Args) -> Self {
+        Self { args }
+    }
+
+    /// Parses a JSON file and imports it as the given table.
+    ///
+    /// # Arguments
+    /// * `conn` - Connection to import into.
+    /// * `input_file` - The input file.
+    /// * `table_name` - Name of the table to create.
+    /// * `is_ndjson` - Reads one object per line when `true`, a JSON array of objects otherwise.
+    pub fn parse_file(
+        &self,
+        conn: &rusqlite::Connection,
+        input_file: &InputFile,
+        table_name: &str,
+        is_ndjson: bool,
+    ) -> anyhow::Result<()> {
+        debug!("Parsing JSON file: {}, NDJSON: {}", input_file, is_ndjson);
+
+        // First pass: collect column names and infer their types
+        let mut schema = TableSchema::new(
+            DetectOptions::from(self.args),
+            self.args.in_schema.table(table_name),
+        );
+        self.scan_file(input_file, is_ndjson, &mut schema)?;
+        schema.finish(table_name)?;
+
+        // Second pass: create the table and insert rows
+        let loader = TableLoader::new(conn, table_name, &self.args.raw_id, &schema)?;
+        let record_count = self.load_file(input_file, is_ndjson, &schema, &loader, None)?;
+        loader.finish()?;
+
+        debug!(
+            "Finished loading {} JSON objects into table: {}",
+            record_count, table_name
+        );
+        Ok(())
+    }
+
+    /// Scans a file into a schema shared by several inputs.
+    ///
+    /// # Returns
+    /// An error if the file contains no objects.
+    pub fn scan_file(
+        &self,
+        input_file: &InputFile,
+        is_ndjson: bool,
+        schema: &mut TableSchema,
+    ) -> anyhow::Result<()> {
+        let pb = Self::progress_bar(input_file)?;
+        pb.set_message(format!("Scanning JSON: {}", input_file));
+
+        let mut record_count: usize = 0;
+        self.for_each_record(input_file, is_ndjson, &pb, |record| {
+            for (name, value) in record {
+                let index = schema.column_index(&name);
+                let result = match value {
+                    Some((value, Some(data_type))) => {
+                        schema.observe_typed(index, &value, data_type)
+                    }
+                    Some((value, None)) => schema.observe(index, Some(&value)),
+                    None => schema.observe(index, None),
+                };
+                result.with_context(|| {
+                    format!(
+                        "Invalid data in object {} of {}",
+                        record_count + 1,
+                        input_file
+                    )
+                })?;
+            }
+            schema.end_row();
+            record_count += 1;
+            Ok(())
+        })?;
+
+        pb.finish_and_clear();
+        if record_count == 0 {
+            return Err(anyhow::anyhow!("No JSON objects found in {}", input_file));
+        }
+        Ok(())
+    }
+
+    /// Inserts the objects of a file scanned by `scan_file`.
+    ///
+    /// # Arguments
+    /// * `source` - Column index and value filled in for every object, e.g. the file name.
+    ///
+    /// # Returns
+    /// The number of rows inserted.
+    pub fn load_file(
+        &self,
+        input_file: &InputFile,
+        is_ndjson: bool,
+        schema: &TableSchema,
+        loader: &TableLoader,
+        source: Option<(usize, &str)>,
+    ) -> anyhow::Result<usize> {
+        let pb = Self::progress_bar(input_file)?;
+        pb.set_message(format!("Importing data from {}", input_file));
+
+        let mut record_count: usize = 0;
+        self.for_each_record(input_file, is_ndjson, &pb, |record| {
+            let mut values: Vec<Option<&str>> = vec![None; schema.headers.len()];
+            for (name, value) in &record {
+                if let Some(index) = schema.position(name) {
+                    values[index] = value.as_ref().map(|(value, _)| value.as_str());
+                }
+            }
+            if let Some((index, value)) = source {
+                values[index] = Some(value);
+            }
+            record_count += 1;
+            loader.insert(&values)
+        })?;
+
+        pb.finish_and_clear();
+        Ok(record_count)
+    }
+
+    /// Creates a progress bar for the size of the file.
+    fn progress_bar(input_file: &InputFile) -> anyhow::Result<indicatif::ProgressBar> {
+        let pb = indicatif::ProgressBar::new(input_size(input_file)?);
+        pb.set_style(STYLE_BAR.clone());
+        Ok(pb)
+    }
+
+    /// Reads the file, decompressing it if needed, and calls `f` with every object, flattened.
+    fn for_each_record<F: FnMut(FlatRecord) -> anyhow::Result<()>>(
+        &self,
+        input_file: &InputFile,
+        is_ndjson: bool,
+        pb: &indicatif::ProgressBar,
+        mut f: F,
+    ) -> anyhow::Result<()> {
+        with_input_stream(input_file, pb, |reader| {
+            let buf_reader = std::io::BufReader::new(reader);
+
+            if is_ndjson {
+                for (line_index, line) in buf_reader.lines().enumerate() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let value: serde_json::Value = serde_json::from_str(&line)
+                        .context(format!("Invalid JSON at line {}", line_index + 1))?;
+                    let record = flatten_object(value, &self.args.in_null)
+                        .context(format!("Line {}", line_index + 1))?;
+                    f(record)?;
+                }
+            } else {
+                let mut deserializer = serde_json::Deserializer::from_reader(buf_reader);
+                serde::Deserializer::deserialize_seq(
+                    &mut deserializer,
+                    ArrayVisitor(|value| f(flatten_object(value, &self.args.in_null)?)),
+                )
+                .context(format!("Invalid JSON array in {}", input_file))?;
+                deserializer.end()?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Visits the elements of a JSON array one by one without collecting them.
+struct ArrayVisitor<F>(F);
+
+impl<'de, F: FnMut(serde_json::Value) -> anyhow::Result<()>> serde::de::Visitor<'de>
+    for ArrayVisitor<F>
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an array of objects")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<serde_json::Value>()? {
+            (self.0)(value).map_err(|err| serde::de::Error::custom(format!("{:#}", err)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Flattens a JSON object into column names and text values.
+///
+/// # Arguments
+/// * `null_tokens` - Strings stored as `NULL`, given with `--in-null`.
+///
+/// # Returns
+/// An error if the value is not an object, or if flattened names collide,
+/// e.g. `{"a.b": 1, "a": {"b": 2}}`.
+fn flatten_object(value: serde_json::Value, null_tokens: &[String]) -> anyhow::Result<FlatRecord> {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut record: FlatRecord = vec![];
+            let mut names: HashSet<String> = HashSet::new();
+            flatten_into(&mut record, &mut names, None, object, null_tokens)?;
+            Ok(record)
+        }
+        other => Err(anyhow::anyhow!("Expected a JSON object, found: {}", other)),
+    }
+}
+
+/// Appends the members of an object, prefixing nested names with their parent key.
+fn flatten_into(
+    record: &mut FlatRecord,
+    names: &mut HashSet<String>,
+    prefix: Option<&str>,
+    object: serde_json::Map<String, serde_json::Value>,
+    null_tokens: &[String],
+) -> anyhow::Result<()> {
+    for (key, value) in object {
+        let name = match prefix {
+            None => key,
+            Some(prefix) => format!("{}.{}", prefix, key),
+        };
+
+        let value = match value {
+            serde_json::Value::Object(nested) if !nested.is_empty() => {
+                flatten_into(record, names, Some(&name), nested, null_tokens)?;
+                continue;
+            }
+            serde_json::Value::Null => None,
+            serde_json::Value::Bool(value) => {
+                Some((if value { "1" } else { "0" }.to_string(), None))
+            }
+            serde_json::Value::Number(value) => Some((value.to_string(), None)),
+            serde_json::Value::String(value) if null_tokens.contains(&value) => None,
+            serde_json::Value::String(value) => Some((value, Some(SqliteDataType::Text))),
+            // Arrays and empty objects
+            json => Some((json.to_string(), Some(SqliteDataType::Text))),
+        };
+        if !names.insert(name.clone()) {
+            return Err(anyhow::anyhow!("Duplicate column name \"{}\"", name));
+        }
+        record.push((name, value));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_flatten_object() {
+    let value = serde_json::json!({
+        "id": 1,
+        "address": {"city": "Tokyo", "geo": {"lat": 35.6}},
+        "tags": ["a", "b"],
+        "active": true,
+        "note": null,
+        "zip": "00123",
+        "missing": "NA",
+        "extra": {}
+    });
+
+    let text = |value: &str| Some((value.to_string(), Some(SqliteDataType::Text)));
+    let detected = |value: &str| Some((value.to_string(), None));
+    let record = flatten_object(value, &["NA".to_string()]).unwrap();
+    let expected: FlatRecord = vec![
+        ("id".to_string(), detected("1")),
+        ("address.city".to_string(), text("Tokyo")),
+        ("address.geo.lat".to_string(), detected("35.6")),
+        ("tags".to_string(), text("[\"a\",\"b\"]")),
+        ("active".to_string(), detected("1")),
+        ("note".to_string(), None),
+        ("zip".to_string(), text("00123")),
+        ("missing".to_string(), None),
+        ("extra".to_string(), text("{}")),
+    ];
+    assert_eq!(record, expected);
+    assert!(flatten_object(serde_json::json!([1]), &[]).is_err());
+
+    let err = flatten_object(serde_json::json!({"a.b": 1, "a": {"b": 2}}), &[]).unwrap_err();
+    assert_eq!(err.to_string(), "Duplicate column name \"a.b\"");
+}
@@ -0,0 +1,119 @@
+use crate::args_util::args::Args;
+use crate::args_util::blob_format::BlobFormat;
+use crate::db::sqlite_value::SqliteValue;
+use crate::output::output_stream::{OutputStream, open_output_stream};
+use crate::output::result_writer::ResultWriter;
+use anyhow::Context;
+use std::io::Write;
+use tracing::debug;
+
+/// Writes query results as JSON objects keyed by column name.
+///
+/// Produces either a JSON array of objects or NDJSON (one object per line).
+/// Numbers are written as JSON numbers, `NULL` as `null` and BLOBs as encoded strings.
+pub struct JsonWriter {
+    writer: OutputStream,
+    /// Column names, already encoded as JSON strings.
+    keys: Vec<String>,
+    /// Writes one object per line instead of an array.
+    is_ndjson: bool,
+    /// Number of rows written so far.
+    row_count: usize,
+    /// Text encoding for BLOB values.
+    blob_format: BlobFormat,
+}
+
+impl JsonWriter {
+    /// Creates a new JsonWriter based on the provided arguments.
+    ///
+    /// # Arguments
+    /// * `args` - Application arguments selecting the output destination.
+    /// * `is_ndjson` - Writes NDJSON when `true`, a JSON array otherwise.
+    pub fn new(args: &Args, is_ndjson: bool) -> anyhow::Result<Self> {
+        debug!("Initializing JsonWriter. NDJSON: {}", is_ndjson);
+        Ok(Self {
+            writer: open_output_stream(args)?,
+            keys: vec![],
+            is_ndjson,
+            row_count: 0,
+            blob_format: args.out_blob_format,
+        })
+    }
+
+    /// Writes a single value in its JSON representation.
+    fn write_value(&mut self, value: &SqliteValue) -> anyhow::Result<()> {
+        match value {
+            SqliteValue::Null => self.writer.write_all(b"null")?,
+            SqliteValue::Integer(value) => write!(self.writer, "{}", value)?,
+            SqliteValue::Real(value) => match serde_json::Number::from_f64(*value) {
+                Some(number) => write!(self.writer, "{}", number)?,
+                None => self.writer.write_all(b"null")?, // NaN and infinity
+            },
+            SqliteValue::Text(value) => serde_json::to_writer(&mut self.writer, value)?,
+            SqliteValue::Blob(value) => {
+                serde_json::to_writer(&mut self.writer, &self.blob_format.encode(value))?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ResultWriter for JsonWriter {
+    /// Keeps the column names as object keys. Always used, even without a header row.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        _declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        self.keys = header
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<_, _>>()
+            .context("Failed to encode column names")?;
+        Ok(())
+    }
+
+    /// Writes a single row as a JSON object.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        if !self.is_ndjson {
+            let separator: &[u8] = if self.row_count == 0 { b"[\n" } else { b",\n" };
+            self.writer.write_all(separator)?;
+        }
+
+        self.writer.write_all(b"{")?;
+        for (index, value) in row.iter().enumerate() {
+            if index > 0 {
+                self.writer.write_all(b",")?;
+            }
+            let key = self
+                .keys
+                .get(index)
+                .context(format!("Column name at pos {} not found", index))?;
+            self.writer.write_all(key.as_bytes())?;
+            self.writer.write_all(b":")?;
+            self.write_value(value)?;
+        }
+        self.writer.write_all(b"}")?;
+
+        if self.is_ndjson {
+            self.writer.write_all(b"\n")?;
+        }
+        self.row_count += 1;
+        Ok(())
+    }
+
+    /// Closes the JSON array, flushes the output and completes compressed output.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if !self.is_ndjson {
+            let closing: &[u8] = if self.row_count == 0 {
+                b"[]\n"
+            } else {
+                b"\n]\n"
+            };
+            self.writer.write_all(closing)?;
+        }
+        self.writer.flush().context("Failed to flush writer")?;
+        self.writer.finish().context("Failed to finish output")?;
+        Ok(())
+    }
+}
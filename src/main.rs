@@ -11,7 +11,12 @@ mod csv;
 mod db;
 mod format;
 mod help;
+mod input;
+mod json;
 mod logger;
+mod output;
+mod parquet;
+mod spreadsheet;
 mod tui;
 
 /// Application entry point
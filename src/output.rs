@@ -0,0 +1,7 @@
+pub mod html_writer;
+pub mod latex_writer;
+pub mod output_stream;
+pub mod result_writer;
+pub mod routed_outputs;
+pub mod sql_writer;
+pub mod table_writer;
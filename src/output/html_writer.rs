@@ -0,0 +1,119 @@
+use crate::args_util::args::Args;
+use crate::args_util::blob_format::BlobFormat;
+use crate::db::sqlite_value::SqliteValue;
+use crate::output::output_stream::{OutputStream, open_output_stream};
+use crate::output::result_writer::ResultWriter;
+use anyhow::Context;
+use std::io::Write;
+use tracing::debug;
+
+/// Writes query results as an HTML `<table>` to paste into wikis and pages.
+///
+/// The table needs no stylesheet or script. Cells are escaped, and classes are
+/// set for styling: the table gets `--out-html-class`, number cells `number`
+/// and `NULL` cells `null`.
+pub struct HtmlWriter {
+    writer: OutputStream,
+    /// Class attribute of the table, already escaped.
+    table_class: Option<String>,
+    /// Text written for `NULL`.
+    null: String,
+    /// Text encoding for BLOB values.
+    blob_format: BlobFormat,
+    /// Skips the header row when `true`.
+    without_header: bool,
+}
+
+impl HtmlWriter {
+    /// Creates a new HtmlWriter based on the provided arguments.
+    pub fn new(args: &Args) -> anyhow::Result<Self> {
+        debug!("Initializing HtmlWriter.");
+        Ok(Self {
+            writer: open_output_stream(args)?,
+            table_class: args.out_html_class.as_deref().map(escape_html),
+            null: args.out_null.to_string(),
+            blob_format: args.out_blob_format,
+            without_header: args.out_without_header,
+        })
+    }
+}
+
+impl ResultWriter for HtmlWriter {
+    /// Opens the table and writes the header row in `<thead>`, unless `--out-without-header` is given.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        _declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        match &self.table_class {
+            Some(class) => writeln!(self.writer, "<table class=\"{}\">", class)?,
+            None => writeln!(self.writer, "<table>")?,
+        }
+        if !self.without_header {
+            self.writer.write_all(b"<thead>\n<tr>")?;
+            for name in header {
+                write!(self.writer, "<th>{}</th>", escape_html(name))?;
+            }
+            self.writer.write_all(b"</tr>\n</thead>\n")?;
+        }
+        self.writer.write_all(b"<tbody>\n")?;
+        Ok(())
+    }
+
+    /// Writes a single row as `<tr>`.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        self.writer.write_all(b"<tr>")?;
+        for value in row {
+            match value {
+                SqliteValue::Null => write!(
+                    self.writer,
+                    "<td class=\"null\">{}</td>",
+                    escape_html(&self.null)
+                )?,
+                SqliteValue::Integer(_) | SqliteValue::Real(_) => {
+                    write!(self.writer, "<td class=\"number\">{}</td>", value)?
+                }
+                SqliteValue::Text(_) | SqliteValue::Blob(_) => write!(
+                    self.writer,
+                    "<td>{}</td>",
+                    escape_html(&value.to_text(self.blob_format))
+                )?,
+            }
+        }
+        self.writer.write_all(b"</tr>\n")?;
+        Ok(())
+    }
+
+    /// Closes the table and completes compressed output.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.writer.write_all(b"</tbody>\n</table>\n")?;
+        self.writer.finish().context("Failed to finish output")?;
+        Ok(())
+    }
+}
+
+/// Escapes text for HTML content and attribute values. Line breaks become `<br>`.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("<br>"),
+            '\r' => {}
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html("<b>\"Q&A\"</b>\r\nit's"),
+        "&lt;b&gt;&quot;Q&amp;A&quot;&lt;/b&gt;<br>it&#39;s"
+    );
+}
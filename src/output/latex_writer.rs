@@ -0,0 +1,116 @@
+use crate::args_util::args::Args;
+use crate::args_util::blob_format::BlobFormat;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::sqlite_value::SqliteValue;
+use crate::output::output_stream::{OutputStream, open_output_stream};
+use crate::output::result_writer::ResultWriter;
+use anyhow::Context;
+use std::io::Write;
+use tracing::debug;
+
+/// Writes query results as a LaTeX `tabular` environment for papers and reports.
+///
+/// Columns declared as `INTEGER` or `REAL` are aligned right, others left.
+/// Special characters are escaped, so the table compiles without extra packages.
+pub struct LatexWriter {
+    writer: OutputStream,
+    /// Text written for `NULL`.
+    null: String,
+    /// Text encoding for BLOB values.
+    blob_format: BlobFormat,
+    /// Skips the header row when `true`.
+    without_header: bool,
+}
+
+impl LatexWriter {
+    /// Creates a new LatexWriter based on the provided arguments.
+    pub fn new(args: &Args) -> anyhow::Result<Self> {
+        debug!("Initializing LatexWriter.");
+        Ok(Self {
+            writer: open_output_stream(args)?,
+            null: args.out_null.to_string(),
+            blob_format: args.out_blob_format,
+            without_header: args.out_without_header,
+        })
+    }
+}
+
+impl ResultWriter for LatexWriter {
+    /// Begins the environment with the column alignments, and writes the header row
+    /// unless `--out-without-header` is given.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        let alignments: String = declared_types
+            .iter()
+            .map(|declared_type| {
+                match declared_type
+                    .as_deref()
+                    .and_then(SqliteDataType::from_declared_type)
+                {
+                    Some(SqliteDataType::Integer | SqliteDataType::Real) => 'r',
+                    _ => 'l',
+                }
+            })
+            .collect();
+        writeln!(self.writer, "\\begin{{tabular}}{{{}}}", alignments)?;
+        writeln!(self.writer, "\\hline")?;
+        if !self.without_header {
+            let names: Vec<String> = header.iter().map(|name| escape_latex(name)).collect();
+            writeln!(self.writer, "{} \\\\", names.join(" & "))?;
+            writeln!(self.writer, "\\hline")?;
+        }
+        Ok(())
+    }
+
+    /// Writes a single row, with cells separated by `&`.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|value| match value {
+                SqliteValue::Null => escape_latex(&self.null),
+                value => escape_latex(&value.to_text(self.blob_format)),
+            })
+            .collect();
+        writeln!(self.writer, "{} \\\\", cells.join(" & "))?;
+        Ok(())
+    }
+
+    /// Ends the environment and completes compressed output.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        writeln!(self.writer, "\\hline")?;
+        writeln!(self.writer, "\\end{{tabular}}")?;
+        self.writer.finish().context("Failed to finish output")?;
+        Ok(())
+    }
+}
+
+/// Escapes the special characters of LaTeX. Line breaks become spaces.
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' => escaped.push(' '),
+            '\r' => {}
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn test_escape_latex() {
+    assert_eq!(
+        escape_latex("50% of $x_1 & {y}\\n ~^#"),
+        "50\\% of \\$x\\_1 \\& \\{y\\}\\textbackslash{}n \\textasciitilde{}\\textasciicircum{}\\#"
+    );
+}
@@ -0,0 +1,206 @@
+use crate::args_util::args::Args;
+use crate::args_util::compression::Compression;
+use anyhow::Context;
+use encoding_rs_rw::EncodingWriter;
+use encoding_rs_rw::misc::DefaultBuffer;
+use std::io::Write;
+use tracing::debug;
+
+/// Output destination with the configured character encoding and compression.
+///
+/// Call `finish` after the last write, so that compressed output is completed.
+pub struct OutputStream {
+    /// Writer of the content; `None` after `finish`.
+    writer: Option<StreamWriter>,
+}
+
+/// Writer of text encoded with `--out-encoding`, or of binary content as is.
+enum StreamWriter {
+    Text(EncodingWriter<DefaultBuffer<CompressWriter>>),
+    Binary(CompressWriter),
+}
+
+impl OutputStream {
+    /// Writes all buffered bytes and completes the compressed stream, if any.
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        let compress_writer = match self.writer.take() {
+            None => return Ok(()),
+            Some(StreamWriter::Binary(compress_writer)) => compress_writer,
+            Some(StreamWriter::Text(mut writer)) => {
+                writer.flush()?;
+                writer.unwrap_writer().map_err(|(_, mut errors)| {
+                    errors
+                        .find_map(|result| result.err())
+                        .unwrap_or_else(|| std::io::Error::other("Failed to write the output"))
+                })?
+            }
+        };
+        compress_writer.finish()
+    }
+
+    /// Returns the writer, or an error after `finish`.
+    fn writer(&mut self) -> std::io::Result<&mut dyn Write> {
+        match &mut self.writer {
+            Some(StreamWriter::Text(writer)) => Ok(writer),
+            Some(StreamWriter::Binary(writer)) => Ok(writer),
+            None => Err(std::io::Error::other("Output is already finished")),
+        }
+    }
+}
+
+impl Write for OutputStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer()?.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.writer {
+            Some(_) => self.writer()?.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Streaming compressor in front of the output destination.
+enum CompressWriter {
+    Plain(Box<dyn Write>),
+    Gzip(flate2::write::GzEncoder<Box<dyn Write>>),
+    Zstd(zstd::stream::write::Encoder<'static, Box<dyn Write>>),
+    Bzip2(bzip2::write::BzEncoder<Box<dyn Write>>),
+    Xz(xz2::write::XzEncoder<Box<dyn Write>>),
+}
+
+impl CompressWriter {
+    /// Wraps the destination with a compressor, or none if `compression` is `None`.
+    fn new(compression: Option<Compression>, writer: Box<dyn Write>) -> std::io::Result<Self> {
+        Ok(match compression {
+            None => CompressWriter::Plain(writer),
+            Some(Compression::Gzip) => CompressWriter::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+            Some(Compression::Zstd) => {
+                CompressWriter::Zstd(zstd::stream::write::Encoder::new(writer, 0)?)
+            }
+            Some(Compression::Bzip2) => CompressWriter::Bzip2(bzip2::write::BzEncoder::new(
+                writer,
+                bzip2::Compression::default(),
+            )),
+            Some(Compression::Xz) => CompressWriter::Xz(xz2::write::XzEncoder::new(writer, 6)),
+        })
+    }
+
+    /// Writes the end of the compressed stream and flushes the destination.
+    fn finish(self) -> std::io::Result<()> {
+        let mut writer = match self {
+            CompressWriter::Plain(writer) => writer,
+            CompressWriter::Gzip(encoder) => encoder.finish()?,
+            CompressWriter::Zstd(encoder) => encoder.finish()?,
+            CompressWriter::Bzip2(encoder) => encoder.finish()?,
+            CompressWriter::Xz(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl Write for CompressWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            CompressWriter::Plain(writer) => writer.write(buf),
+            CompressWriter::Gzip(encoder) => encoder.write(buf),
+            CompressWriter::Zstd(encoder) => encoder.write(buf),
+            CompressWriter::Bzip2(encoder) => encoder.write(buf),
+            CompressWriter::Xz(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            CompressWriter::Plain(writer) => writer.flush(),
+            CompressWriter::Gzip(encoder) => encoder.flush(),
+            CompressWriter::Zstd(encoder) => encoder.flush(),
+            CompressWriter::Bzip2(encoder) => encoder.flush(),
+            CompressWriter::Xz(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Opens the output destination with the configured character encoding.
+///
+/// The output is compressed with `--out-compress`, or as given by a `.gz`,
+/// `.zst`, `.bz2` or `.xz` extension of `--out-file`.
+///
+/// # Returns
+/// A writer to the file given by `--out-file`, or to stdout if it is omitted.
+pub fn open_output_stream(args: &Args) -> anyhow::Result<OutputStream> {
+    let encoder = output_encoding(args)?.new_encoder();
+
+    let compress_writer = open_destination(args)?;
+    Ok(OutputStream {
+        writer: Some(StreamWriter::Text(EncodingWriter::new(
+            compress_writer,
+            encoder,
+        ))),
+    })
+}
+
+/// Resolves the character encoding given by `--out-encoding`.
+pub fn output_encoding(args: &Args) -> anyhow::Result<&'static encoding_rs::Encoding> {
+    encoding_rs::Encoding::for_label(args.out_encoding.to_lowercase().as_bytes())
+        .context(anyhow::anyhow!("Invalid encoding: {}", args.out_encoding))
+}
+
+/// Opens the output destination for binary content such as a workbook.
+///
+/// `--out-encoding` does not apply, but the output is compressed like text output.
+pub fn open_binary_output_stream(args: &Args) -> anyhow::Result<OutputStream> {
+    Ok(OutputStream {
+        writer: Some(StreamWriter::Binary(open_destination(args)?)),
+    })
+}
+
+/// Opens `--out-file`, or stdout if it is omitted, with the compression of the output.
+fn open_destination(args: &Args) -> anyhow::Result<CompressWriter> {
+    // Determine output target: stdout or file
+    let writer: Box<dyn Write> = match &args.out_file {
+        None => {
+            debug!("Output to stdout.");
+            Box::new(std::io::stdout())
+        }
+        Some(output_file) => {
+            debug!("Output to file: {}", output_file);
+            let file = std::fs::File::create(output_file)
+                .context(format!("Failed to create {}", output_file))?;
+            Box::new(file)
+        }
+    };
+
+    let compression = args.out_compress.or_else(|| {
+        args.out_file
+            .as_deref()
+            .and_then(Compression::from_filename)
+    });
+    debug!("Output compression: {:?}", compression);
+    Ok(CompressWriter::new(compression, writer)?)
+}
+
+#[test]
+fn test_compress_writer() {
+    use std::io::Read;
+
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    let file = std::fs::File::create(&path).unwrap();
+    let mut writer = CompressWriter::new(Some(Compression::Zstd), Box::new(file)).unwrap();
+    writer.write_all(b"id\n1\n").unwrap();
+    writer.finish().unwrap();
+
+    let compressed = std::fs::read(&path).unwrap();
+    let compression = Compression::from_magic(&compressed);
+    assert_eq!(compression, Some(Compression::Zstd));
+    let mut content = String::new();
+    Compression::decoder(compression, compressed.as_slice())
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "id\n1\n");
+}
@@ -0,0 +1,185 @@
+use crate::args_util::args::Args;
+use crate::args_util::compression::strip_compression_extension;
+use crate::args_util::out_format::OutFormat;
+use crate::csv::csv_writer::CsvWriter;
+use crate::db::sqlite_value::SqliteValue;
+use crate::db::statement_result::StatementResult;
+use crate::json::json_writer::JsonWriter;
+use crate::output::html_writer::HtmlWriter;
+use crate::output::latex_writer::LatexWriter;
+use crate::output::sql_writer::SqlWriter;
+use crate::output::table_writer::{TableStyle, TableWriter};
+use crate::parquet::parquet_writer::ParquetWriter;
+use crate::spreadsheet::xlsx_writer::XlsxWriter;
+use tracing::debug;
+
+/// Writes query results in a specific output format.
+pub trait ResultWriter {
+    /// Writes the column names. Called once before any row, or for Excel
+    /// output once per result of `--out-all-results`.
+    ///
+    /// # Arguments
+    /// * `header` - Column names.
+    /// * `declared_types` - Declared type of each column, if any, for formats with typed cells.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        declared_types: &[Option<String>],
+    ) -> anyhow::Result<()>;
+
+    /// Writes a single row.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()>;
+
+    /// Completes the output and flushes it.
+    fn finish(&mut self) -> anyhow::Result<()>;
+}
+
+/// Creates a writer for the output format selected by the arguments.
+///
+/// The format is taken from `--out-format`, or detected from the `--out-file` extension.
+pub fn create_result_writer(args: &Args) -> anyhow::Result<Box<dyn ResultWriter>> {
+    let out_format = OutFormat::resolve(args.out_format, &args.out_file);
+    debug!("Creating result writer for format: {:?}", out_format);
+
+    let writer: Box<dyn ResultWriter> = match out_format {
+        OutFormat::Csv => Box::new(CsvWriter::new(args)?),
+        OutFormat::Json => Box::new(JsonWriter::new(args, false)?),
+        OutFormat::Ndjson => Box::new(JsonWriter::new(args, true)?),
+        OutFormat::Xlsx => Box::new(XlsxWriter::new(args)?),
+        OutFormat::Parquet => Box::new(ParquetWriter::new(args)?),
+        OutFormat::Table => Box::new(TableWriter::new(args, TableStyle::Plain)?),
+        OutFormat::Markdown => Box::new(TableWriter::new(args, TableStyle::Markdown)?),
+        OutFormat::Box => Box::new(TableWriter::new(args, TableStyle::Box)?),
+        OutFormat::Html => Box::new(HtmlWriter::new(args)?),
+        OutFormat::Latex => Box::new(LatexWriter::new(args)?),
+        OutFormat::Sql => Box::new(SqlWriter::new(args)?),
+    };
+    Ok(writer)
+}
+
+/// Writes the header and all rows of a statement result, then finishes the output.
+pub fn write_statement_result(
+    writer: &mut dyn ResultWriter,
+    statement_result: &StatementResult,
+) -> anyhow::Result<()> {
+    write_rows(writer, statement_result)?;
+    writer.finish()
+}
+
+/// Writes the result of every query for `--out-all-results`.
+///
+/// Excel output gets one worksheet per result. Other formats are written to
+/// numbered files when `--out-file` is given, or to STDOUT separated by blank
+/// lines. Parquet, SQL and compressed output need `--out-file` for more than one result.
+pub fn write_all_results(
+    args: &Args,
+    statement_results: &[&StatementResult],
+) -> anyhow::Result<()> {
+    let out_format = OutFormat::resolve(args.out_format, &args.out_file);
+    debug!(
+        "Writing {} results as {:?}",
+        statement_results.len(),
+        out_format
+    );
+    let mut args = args.clone();
+    args.out_format = Some(out_format);
+    // Several results cannot share a pager
+    args.out_no_pager = true;
+
+    if out_format == OutFormat::Xlsx {
+        if let Some(out_file) = &args.out_file {
+            eprintln!("Saving query results to {}", out_file);
+        }
+        let mut writer = create_result_writer(&args)?;
+        for statement_result in statement_results {
+            write_rows(writer.as_mut(), statement_result)?;
+        }
+        return writer.finish();
+    }
+
+    match args.out_file.clone() {
+        Some(out_file) => {
+            for (index, statement_result) in statement_results.iter().enumerate() {
+                let file_path = numbered_file_path(&out_file, index + 1);
+                eprintln!("Saving query results to {}", file_path);
+                args.out_file = Some(file_path);
+                let mut writer = create_result_writer(&args)?;
+                write_statement_result(writer.as_mut(), statement_result)?;
+            }
+        }
+        None => {
+            // Each result is a complete Parquet file, SQL dump or compressed
+            // stream, which cannot be told apart when joined on STDOUT
+            if statement_results.len() > 1 {
+                let joined = match out_format {
+                    OutFormat::Parquet => Some("Parquet results"),
+                    OutFormat::Sql => Some("SQL results"),
+                    _ if args.out_compress.is_some() => Some("compressed results"),
+                    _ => None,
+                };
+                if let Some(joined) = joined {
+                    return Err(anyhow::anyhow!(
+                        "Several {} cannot be written to STDOUT. Give --out-file to write numbered files.",
+                        joined
+                    ));
+                }
+            }
+            for (index, statement_result) in statement_results.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                let mut writer = create_result_writer(&args)?;
+                write_statement_result(writer.as_mut(), statement_result)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the header and all rows of a statement result, keeping the output open.
+pub fn write_rows(
+    writer: &mut dyn ResultWriter,
+    statement_result: &StatementResult,
+) -> anyhow::Result<()> {
+    writer.write_header(&statement_result.header, &statement_result.declared_types)?;
+    for row in &statement_result.rows {
+        writer.write_row(row)?;
+    }
+    Ok(())
+}
+
+/// Returns the path of the `number`-th result file of `--out-all-results`.
+///
+/// `{n}` in the path is replaced with the number. Otherwise the number is put
+/// before the extension, so `out.csv.gz` becomes `out.1.csv.gz`.
+fn numbered_file_path(out_file: &str, number: usize) -> String {
+    if out_file.contains("{n}") {
+        return out_file.replace("{n}", &number.to_string());
+    }
+    let inner = strip_compression_extension(out_file);
+    let compression_extension = &out_file[inner.len()..];
+    match std::path::Path::new(inner).extension() {
+        Some(extension) => {
+            let stem = &inner[..inner.len() - extension.len() - 1];
+            format!(
+                "{}.{}.{}{}",
+                stem,
+                number,
+                extension.to_string_lossy(),
+                compression_extension
+            )
+        }
+        None => format!("{}.{}{}", inner, number, compression_extension),
+    }
+}
+
+#[test]
+fn test_numbered_file_path() {
+    assert_eq!(numbered_file_path("out.csv", 1), "out.1.csv");
+    assert_eq!(
+        numbered_file_path("data.v2/out.csv.gz", 2),
+        "data.v2/out.2.csv.gz"
+    );
+    assert_eq!(numbered_file_path("out", 3), "out.3");
+    assert_eq!(numbered_file_path("out-{n}.json", 4), "out-4.json");
+}
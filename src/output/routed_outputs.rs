@@ -0,0 +1,80 @@
+use crate::args_util::args::Args;
+use crate::args_util::out_format::OutFormat;
+use crate::db::statement_result::StatementResult;
+use crate::output::result_writer::{
+    ResultWriter, create_result_writer, write_rows, write_statement_result,
+};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Writes results routed to files by `-- @output` and `.output` annotations.
+///
+/// The format and compression of each file are detected from its name, while
+/// other output options apply as given. A file takes one result: `{n}` in the
+/// name numbers the files, and Excel workbooks get one worksheet per result.
+/// Progress goes to stderr, since the default output may be stdout.
+pub struct RoutedOutputs {
+    args: Args,
+    /// Number of results routed to each annotated name.
+    counts: HashMap<String, usize>,
+    /// Excel workbooks kept open for more worksheets, by file path.
+    workbooks: HashMap<String, Box<dyn ResultWriter>>,
+}
+
+impl RoutedOutputs {
+    /// Creates the routed outputs with the output options of the arguments.
+    pub fn new(args: &Args) -> Self {
+        let mut args = args.clone();
+        args.out_format = None;
+        args.out_compress = None;
+        Self {
+            args,
+            counts: HashMap::new(),
+            workbooks: HashMap::new(),
+        }
+    }
+
+    /// Writes a statement result to the file of an annotation.
+    pub fn write(
+        &mut self,
+        output: &str,
+        statement_result: &StatementResult,
+    ) -> anyhow::Result<()> {
+        let count = self.counts.entry(output.to_string()).or_insert(0);
+        *count += 1;
+        let file_path = output.replace("{n}", &count.to_string());
+        debug!("Routing result {} of {} to {}", count, output, file_path);
+
+        let out_file = Some(file_path.clone());
+        if OutFormat::resolve(None, &out_file) == OutFormat::Xlsx {
+            if let Some(writer) = self.workbooks.get_mut(&file_path) {
+                return write_rows(writer.as_mut(), statement_result);
+            }
+            eprintln!("Saving query results to {}", file_path);
+            self.args.out_file = out_file;
+            let mut writer = create_result_writer(&self.args)?;
+            write_rows(writer.as_mut(), statement_result)?;
+            self.workbooks.insert(file_path, writer);
+            return Ok(());
+        }
+
+        if *count > 1 && !output.contains("{n}") {
+            return Err(anyhow::anyhow!(
+                "{} already holds the result of an earlier statement. Put {{n}} in the name to number the files, or use an .xlsx file for one worksheet per result.",
+                file_path
+            ));
+        }
+        eprintln!("Saving query results to {}", file_path);
+        self.args.out_file = out_file;
+        let mut writer = create_result_writer(&self.args)?;
+        write_statement_result(writer.as_mut(), statement_result)
+    }
+
+    /// Writes the Excel workbooks.
+    pub fn finish(&mut self) -> anyhow::Result<()> {
+        for (_, mut writer) in self.workbooks.drain() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+use crate::args_util::args::Args;
+use crate::args_util::compression::strip_compression_extension;
+use crate::args_util::sql_dialect::SqlDialect;
+use crate::db::inferred_type::InferredType;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::sqlite_quoted::SqliteQuoted;
+use crate::db::sqlite_value::SqliteValue;
+use crate::db::temporal_value::TemporalValue;
+use crate::output::output_stream::{OutputStream, open_output_stream};
+use crate::output::result_writer::ResultWriter;
+use anyhow::Context;
+use std::io::Write;
+use tracing::debug;
+
+/// Table name when neither `--out-sql-table` nor `--out-file` is given.
+const DEFAULT_TABLE_NAME: &str = "result";
+
+/// Type of a column in the `CREATE TABLE` statement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColumnType {
+    Typed(SqliteDataType),
+    /// Numbers without a declared type, given the widest numeric type of the dialect.
+    Numeric,
+    Blob,
+    /// No declared type and no values to infer it from.
+    Untyped,
+}
+
+/// Writes query results as a `CREATE TABLE` statement and `INSERT` statements,
+/// to seed a database in the dialect of `--out-sql-dialect`.
+///
+/// Column types come from the declared types of the result. Columns without a
+/// declared type, such as expressions, take the type of their values in the
+/// first batch of rows, with numbers always given the widest numeric type. Each `INSERT` adds up to `--out-sql-batch-size` rows.
+pub struct SqlWriter {
+    writer: OutputStream,
+    dialect: SqlDialect,
+    /// Table name, already quoted.
+    table_name: String,
+    /// Number of rows per `INSERT` statement.
+    batch_size: usize,
+    /// Column names.
+    header: Vec<String>,
+    /// Declared type of each column.
+    declared_types: Vec<Option<String>>,
+    /// Type of each column; resolved with the first batch.
+    column_types: Vec<ColumnType>,
+    /// Rows not written yet.
+    rows: Vec<Vec<SqliteValue>>,
+    /// Set once the column types are resolved and `CREATE TABLE` is written.
+    started: bool,
+    /// Skips the `CREATE TABLE` statement when `true`.
+    without_header: bool,
+}
+
+impl SqlWriter {
+    /// Creates a new SqlWriter based on the provided arguments.
+    pub fn new(args: &Args) -> anyhow::Result<Self> {
+        debug!(
+            "Initializing SqlWriter with dialect: {:?}",
+            args.out_sql_dialect
+        );
+        let table_name = args
+            .out_sql_table
+            .clone()
+            .or_else(|| args.out_file.as_deref().and_then(file_stem))
+            .unwrap_or_else(|| DEFAULT_TABLE_NAME.to_string());
+        Ok(Self {
+            writer: open_output_stream(args)?,
+            dialect: args.out_sql_dialect,
+            table_name: quote_identifier(&table_name, args.out_sql_dialect),
+            batch_size: args.out_sql_batch_size,
+            header: vec![],
+            declared_types: vec![],
+            column_types: vec![],
+            rows: Vec::with_capacity(args.out_sql_batch_size),
+            started: false,
+            without_header: args.out_without_header,
+        })
+    }
+
+    /// Writes the buffered rows as an `INSERT` statement, preceded by `CREATE TABLE` for the first batch.
+    fn write_batch(&mut self) -> anyhow::Result<()> {
+        if !self.started {
+            self.started = true;
+            self.column_types = (0..self.header.len())
+                .map(|column_index| self.column_type(column_index))
+                .collect();
+            if !self.without_header {
+                self.write_create_table()?;
+            }
+        }
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+
+        let columns: Vec<String> = self
+            .header
+            .iter()
+            .map(|name| quote_identifier(name, self.dialect))
+            .collect();
+        writeln!(
+            self.writer,
+            "INSERT INTO {} ({}) VALUES",
+            self.table_name,
+            columns.join(", ")
+        )?;
+        for (row_index, row) in self.rows.iter().enumerate() {
+            let values: Vec<String> = row
+                .iter()
+                .zip(&self.column_types)
+                .map(|(value, column_type)| literal(value, *column_type, self.dialect))
+                .collect();
+            let terminator = if row_index + 1 == self.rows.len() {
+                ";"
+            } else {
+                ","
+            };
+            writeln!(self.writer, "({}){}", values.join(", "), terminator)?;
+        }
+        self.rows.clear();
+        Ok(())
+    }
+
+    /// Writes the `CREATE TABLE` statement.
+    fn write_create_table(&mut self) -> anyhow::Result<()> {
+        let definitions: Vec<String> = self
+            .header
+            .iter()
+            .enumerate()
+            .map(|(column_index, name)| {
+                let name = quote_identifier(name, self.dialect);
+                // SQLite keeps the declared type as written, e.g. `VARCHAR(20)`
+                let type_name = match (self.dialect, &self.declared_types[column_index]) {
+                    (SqlDialect::Sqlite, Some(declared_type)) => declared_type.to_string(),
+                    _ => type_name(self.column_types[column_index], self.dialect).to_string(),
+                };
+                if type_name.is_empty() {
+                    format!("  {}", name)
+                } else {
+                    format!("  {} {}", name, type_name)
+                }
+            })
+            .collect();
+        writeln!(
+            self.writer,
+            "CREATE TABLE {} (\n{}\n);",
+            self.table_name,
+            definitions.join(",\n")
+        )?;
+        Ok(())
+    }
+
+    /// Returns the type of a column from its declared type, or from the buffered rows.
+    ///
+    /// Numbers are `Numeric`, so a `REAL` in a later batch still fits, and BLOBs
+    /// `BLOB`. Other columns with values are `TEXT`.
+    fn column_type(&self, column_index: usize) -> ColumnType {
+        if let Some(data_type) = self.declared_types[column_index]
+            .as_deref()
+            .and_then(SqliteDataType::from_declared_type)
+        {
+            return ColumnType::Typed(data_type);
+        }
+        match InferredType::infer(self.rows.iter().filter_map(|row| row.get(column_index))) {
+            Some(InferredType::Numeric) => ColumnType::Numeric,
+            Some(InferredType::Blob) => ColumnType::Blob,
+            Some(InferredType::Text) => ColumnType::Typed(SqliteDataType::Text),
+            None => ColumnType::Untyped,
+        }
+    }
+}
+
+impl ResultWriter for SqlWriter {
+    /// Keeps the column names and types for `CREATE TABLE`, written with the first batch.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        self.header = header.to_vec();
+        self.declared_types = declared_types.to_vec();
+        self.declared_types.resize(header.len(), None);
+        Ok(())
+    }
+
+    /// Buffers a row, and writes an `INSERT` statement when the batch is full.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        self.rows.push(row.to_vec());
+        if self.rows.len() >= self.batch_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the remaining rows and completes compressed output.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.write_batch()?;
+        self.writer.finish().context("Failed to finish output")?;
+        Ok(())
+    }
+}
+
+/// Returns the file name without the compression and format extensions, e.g. `customers` for `data/customers.sql.gz`.
+fn file_stem(file_path: &str) -> Option<String> {
+    std::path::Path::new(strip_compression_extension(file_path))
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+}
+
+/// Quotes a table or column name.
+fn quote_identifier(name: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::Sqlite => SqliteQuoted::Field(name.to_string()).get(),
+        SqlDialect::Postgres => format!("\"{}\"", name.replace('"', "\"\"")),
+        SqlDialect::Mysql => format!("`{}`", name.replace('`', "``")),
+    }
+}
+
+/// Returns the name of a column type in the dialect; empty for an untyped SQLite column.
+fn type_name(column_type: ColumnType, dialect: SqlDialect) -> &'static str {
+    match (column_type, dialect) {
+        (ColumnType::Typed(data_type), SqlDialect::Sqlite) => match data_type {
+            SqliteDataType::Integer => "INTEGER",
+            SqliteDataType::Real => "REAL",
+            SqliteDataType::Text => "TEXT",
+            SqliteDataType::Boolean => "BOOLEAN",
+            SqliteDataType::Date => "DATE",
+            SqliteDataType::Datetime => "DATETIME",
+            SqliteDataType::Time => "TIME",
+        },
+        (ColumnType::Typed(data_type), SqlDialect::Postgres) => match data_type {
+            SqliteDataType::Integer => "BIGINT",
+            SqliteDataType::Real => "DOUBLE PRECISION",
+            SqliteDataType::Text => "TEXT",
+            SqliteDataType::Boolean => "BOOLEAN",
+            SqliteDataType::Date => "DATE",
+            SqliteDataType::Datetime => "TIMESTAMP",
+            SqliteDataType::Time => "TIME",
+        },
+        (ColumnType::Typed(data_type), SqlDialect::Mysql) => match data_type {
+            SqliteDataType::Integer => "BIGINT",
+            SqliteDataType::Real => "DOUBLE",
+            SqliteDataType::Text => "LONGTEXT",
+            SqliteDataType::Boolean => "BOOLEAN",
+            SqliteDataType::Date => "DATE",
+            // Keep fractional seconds, which MySQL drops by default
+            SqliteDataType::Datetime => "DATETIME(6)",
+            SqliteDataType::Time => "TIME(6)",
+        },
+        // NUMERIC keeps integers exact; MySQL needs a fixed scale for DECIMAL
+        (ColumnType::Numeric, SqlDialect::Sqlite | SqlDialect::Postgres) => "NUMERIC",
+        (ColumnType::Numeric, SqlDialect::Mysql) => "DOUBLE",
+        (ColumnType::Blob, SqlDialect::Sqlite) => "BLOB",
+        (ColumnType::Blob, SqlDialect::Postgres) => "BYTEA",
+        (ColumnType::Blob, SqlDialect::Mysql) => "LONGBLOB",
+        (ColumnType::Untyped, SqlDialect::Sqlite) => "",
+        (ColumnType::Untyped, SqlDialect::Postgres) => "TEXT",
+        (ColumnType::Untyped, SqlDialect::Mysql) => "LONGTEXT",
+    }
+}
+
+/// Converts a value into a SQL literal for a column of the given type.
+///
+/// SQLite takes values as stored. Other dialects get `TRUE`/`FALSE` for
+/// `BOOLEAN` columns, and ISO 8601 text for dates and times stored as Unix time.
+fn literal(value: &SqliteValue, column_type: ColumnType, dialect: SqlDialect) -> String {
+    if dialect != SqlDialect::Sqlite {
+        match (column_type, value) {
+            (ColumnType::Typed(SqliteDataType::Boolean), SqliteValue::Integer(number)) => {
+                return if *number == 0 { "FALSE" } else { "TRUE" }.to_string();
+            }
+            (
+                ColumnType::Typed(SqliteDataType::Date | SqliteDataType::Datetime),
+                SqliteValue::Integer(_) | SqliteValue::Real(_),
+            ) => {
+                if let Some(temporal) = TemporalValue::from_stored(value) {
+                    let text = match temporal {
+                        TemporalValue::Date(date) => date.format("%Y-%m-%d").to_string(),
+                        TemporalValue::Datetime(datetime) => {
+                            datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+                        }
+                        TemporalValue::Time(time) => time.format("%H:%M:%S%.f").to_string(),
+                    };
+                    return quote_text(&text, dialect);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match value {
+        SqliteValue::Null => "NULL".to_string(),
+        SqliteValue::Integer(number) => number.to_string(),
+        SqliteValue::Real(number) if number.is_finite() => value.to_string(),
+        // SQLite reads numbers beyond the range of REAL as infinity
+        SqliteValue::Real(number) => match dialect {
+            SqlDialect::Postgres if *number > 0.0 => "'Infinity'".to_string(),
+            SqlDialect::Postgres => "'-Infinity'".to_string(),
+            _ if *number > 0.0 => "1e999".to_string(),
+            _ => "-1e999".to_string(),
+        },
+        SqliteValue::Text(text) => quote_text(text, dialect),
+        SqliteValue::Blob(bytes) => {
+            let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            match dialect {
+                SqlDialect::Postgres => format!("'\\x{}'", hex),
+                _ => format!("X'{}'", hex),
+            }
+        }
+    }
+}
+
+/// Quotes a string literal. MySQL also treats backslashes as escapes.
+fn quote_text(text: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::Mysql => SqliteQuoted::Text(text.replace('\\', "\\\\")).get(),
+        _ => SqliteQuoted::Text(text.to_string()).get(),
+    }
+}
+
+#[test]
+fn test_literal() {
+    let text = SqliteValue::Text("it's C:\\".to_string());
+    let untyped = ColumnType::Untyped;
+    assert_eq!(literal(&text, untyped, SqlDialect::Sqlite), "'it''s C:\\'");
+    assert_eq!(literal(&text, untyped, SqlDialect::Mysql), "'it''s C:\\\\'");
+    assert_eq!(
+        literal(
+            &SqliteValue::Blob(vec![0, 255]),
+            ColumnType::Blob,
+            SqlDialect::Postgres
+        ),
+        "'\\x00ff'"
+    );
+
+    let boolean = ColumnType::Typed(SqliteDataType::Boolean);
+    assert_eq!(
+        literal(&SqliteValue::Integer(1), boolean, SqlDialect::Sqlite),
+        "1"
+    );
+    assert_eq!(
+        literal(&SqliteValue::Integer(1), boolean, SqlDialect::Postgres),
+        "TRUE"
+    );
+
+    let date = ColumnType::Typed(SqliteDataType::Date);
+    assert_eq!(
+        literal(&SqliteValue::Integer(86400), date, SqlDialect::Mysql),
+        "'1970-01-02'"
+    );
+    assert_eq!(
+        literal(
+            &SqliteValue::Real(f64::INFINITY),
+            untyped,
+            SqlDialect::Sqlite
+        ),
+        "1e999"
+    );
+}
@@ -0,0 +1,361 @@
+use crate::args_util::args::Args;
+use crate::args_util::blob_format::BlobFormat;
+use crate::db::sqlite_value::SqliteValue;
+use crate::output::output_stream::{OutputStream, open_output_stream, output_encoding};
+use crate::output::result_writer::ResultWriter;
+use anyhow::Context;
+use std::io::{IsTerminal, Write};
+use tracing::debug;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Pager used when `PAGER` is not set: quits if the output fits, keeps colors and long lines.
+const DEFAULT_PAGER: &str = "less -FRSX";
+
+/// Layout of a table rendered for reading.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableStyle {
+    /// Columns separated by spaces, with a dashed line under the header.
+    Plain,
+    /// A GitHub Flavored Markdown table.
+    Markdown,
+    /// Columns framed with box-drawing characters.
+    Box,
+}
+
+/// Writes query results as an aligned table for terminals and documents.
+///
+/// Columns are sized by display width, so East Asian wide characters line up,
+/// and values wider than `--out-max-width` are cut with `…`. Columns holding
+/// only numbers are right-aligned. The table is rendered after the last row,
+/// and shown in a pager if it is printed to a terminal and exceeds its height.
+pub struct TableWriter {
+    /// Output destination; `None` after `finish`.
+    writer: Option<OutputStream>,
+    /// Layout of the table.
+    style: TableStyle,
+    /// Column names.
+    header: Vec<String>,
+    /// Cell text of every row, escaped and truncated.
+    rows: Vec<Vec<String>>,
+    /// `Some(true)` for columns with numbers and no other values except `NULL`,
+    /// `None` for columns with no values yet.
+    numeric: Vec<Option<bool>>,
+    /// Display width at which values are truncated; `0` for no limit.
+    max_width: usize,
+    /// Text shown for `NULL`.
+    null: String,
+    /// Text encoding for BLOB values.
+    blob_format: BlobFormat,
+    /// Skips the header when `true`.
+    without_header: bool,
+    /// Allows a pager for output to a terminal.
+    use_pager: bool,
+    /// Character encoding of the output, also applied to the content of the pager.
+    encoding: &'static encoding_rs::Encoding,
+}
+
+impl TableWriter {
+    /// Creates a new TableWriter based on the provided arguments.
+    pub fn new(args: &Args, style: TableStyle) -> anyhow::Result<Self> {
+        debug!("Initializing TableWriter with style: {:?}", style);
+        Ok(Self {
+            writer: Some(open_output_stream(args)?),
+            style,
+            header: vec![],
+            rows: vec![],
+            numeric: vec![],
+            max_width: args.out_max_width,
+            null: args.out_null.to_string(),
+            blob_format: args.out_blob_format,
+            without_header: args.out_without_header,
+            use_pager: !args.out_no_pager && args.out_file.is_none() && args.out_compress.is_none(),
+            encoding: output_encoding(args)?,
+        })
+    }
+
+    /// Converts a value to the text of a cell.
+    ///
+    /// Line breaks and tabs are escaped so that every row takes one line, `|` is
+    /// escaped in Markdown, and the text is truncated at the maximum width.
+    fn cell_text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for character in text.chars() {
+            match character {
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                '|' if self.style == TableStyle::Markdown => escaped.push_str("\\|"),
+                character if character.is_control() => {}
+                character => escaped.push(character),
+            }
+        }
+        truncate(&escaped, self.max_width)
+    }
+
+    /// Renders the table.
+    fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.header.iter().map(|name| name.width()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.width());
+            }
+        }
+        // Markdown needs at least three dashes in the delimiter row
+        if self.style == TableStyle::Markdown {
+            widths.iter_mut().for_each(|width| *width = (*width).max(3));
+        }
+
+        let mut lines: Vec<String> = vec![];
+        if self.style == TableStyle::Box {
+            lines.push(self.border(&widths, '┌', '┬', '┐'));
+        }
+        if !self.without_header || self.style == TableStyle::Markdown {
+            lines.push(self.line(&self.header, &widths));
+            lines.push(match self.style {
+                TableStyle::Plain => self.border(&widths, '-', '-', '-'),
+                TableStyle::Markdown => self.delimiter_row(&widths),
+                TableStyle::Box => self.border(&widths, '├', '┼', '┤'),
+            });
+        }
+        for row in &self.rows {
+            lines.push(self.line(row, &widths));
+        }
+        if self.style == TableStyle::Box {
+            lines.push(self.border(&widths, '└', '┴', '┘'));
+        }
+
+        let mut content = lines.join("\n");
+        content.push('\n');
+        content
+    }
+
+    /// Renders a row of cells, padded to the column widths.
+    fn line(&self, cells: &[String], widths: &[usize]) -> String {
+        let padded: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(index, width)| {
+                let cell = cells.get(index).map_or("", String::as_str);
+                let padding = " ".repeat(width.saturating_sub(cell.width()));
+                if self.numeric[index] == Some(true) {
+                    format!("{}{}", padding, cell)
+                } else {
+                    format!("{}{}", cell, padding)
+                }
+            })
+            .collect();
+        match self.style {
+            TableStyle::Plain => padded.join("  ").trim_end().to_string(),
+            TableStyle::Markdown => format!("| {} |", padded.join(" | ")),
+            TableStyle::Box => format!("│ {} │", padded.join(" │ ")),
+        }
+    }
+
+    /// Renders a horizontal line with the given corner and joint characters.
+    fn border(&self, widths: &[usize], left: char, joint: char, right: char) -> String {
+        let (fill, padding) = match self.style {
+            TableStyle::Box => ('─', 2),
+            _ => ('-', 0),
+        };
+        let segments: Vec<String> = widths
+            .iter()
+            .map(|width| fill.to_string().repeat(width + padding))
+            .collect();
+        match self.style {
+            // Columns are two spaces apart, matching `line`
+            TableStyle::Plain => segments.join("  "),
+            _ => format!("{}{}{}", left, segments.join(&joint.to_string()), right),
+        }
+    }
+
+    /// Renders the delimiter row of a Markdown table, aligning numeric columns right.
+    fn delimiter_row(&self, widths: &[usize]) -> String {
+        let cells: Vec<String> = widths
+            .iter()
+            .zip(&self.numeric)
+            .map(|(width, numeric)| {
+                if *numeric == Some(true) {
+                    format!("{}:", "-".repeat(width + 1))
+                } else {
+                    "-".repeat(width + 2)
+                }
+            })
+            .collect();
+        format!("|{}|", cells.join("|"))
+    }
+}
+
+impl ResultWriter for TableWriter {
+    /// Keeps the column names; the table is rendered by `finish`.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        _declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        self.header = header.iter().map(|name| self.cell_text(name)).collect();
+        self.numeric = vec![None; header.len()];
+        Ok(())
+    }
+
+    /// Keeps the cell text of a row.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        let cells = row
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let text = match value {
+                    SqliteValue::Null => self.null.to_string(),
+                    SqliteValue::Integer(_) | SqliteValue::Real(_) => {
+                        if let Some(numeric @ None) = self.numeric.get_mut(index) {
+                            *numeric = Some(true);
+                        }
+                        value.to_string()
+                    }
+                    SqliteValue::Text(_) | SqliteValue::Blob(_) => {
+                        if let Some(numeric) = self.numeric.get_mut(index) {
+                            *numeric = Some(false);
+                        }
+                        value.to_text(self.blob_format)
+                    }
+                };
+                self.cell_text(&text)
+            })
+            .collect();
+        self.rows.push(cells);
+        Ok(())
+    }
+
+    /// Renders the table and writes it, through a pager if it does not fit the terminal.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            let content = self.render();
+            if !(self.use_pager
+                && exceeds_terminal(&content)
+                && show_in_pager(&self.encoding.encode(&content).0)?)
+            {
+                writer.write_all(content.as_bytes())?;
+            }
+            writer.finish().context("Failed to finish output")?;
+        }
+        Ok(())
+    }
+}
+
+/// Truncates text to a display width, ending it with `…` when cut.
+///
+/// # Arguments
+/// * `max_width` - Maximum display width; `0` for no limit.
+fn truncate(text: &str, max_width: usize) -> String {
+    if max_width == 0 || text.width() <= max_width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut width: usize = 0;
+    for character in text.chars() {
+        let character_width = character.width().unwrap_or(0);
+        // Leave room for the ellipsis
+        if width + character_width > max_width - 1 {
+            break;
+        }
+        truncated.push(character);
+        width += character_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Returns `true` if STDOUT is a terminal with fewer lines than the content.
+fn exceeds_terminal(content: &str) -> bool {
+    if !std::io::stdout().is_terminal() {
+        return false;
+    }
+    match tuirealm::ratatui::crossterm::terminal::size() {
+        Ok((_, rows)) => content.lines().count() >= rows as usize,
+        Err(_) => false,
+    }
+}
+
+/// Shows the content, already in the output encoding, in the pager given by `PAGER`, or `less`.
+///
+/// # Returns
+/// `false` if no pager could be started, so the content is still to be printed.
+fn show_in_pager(content: &[u8]) -> anyhow::Result<bool> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut words = pager.split_whitespace();
+    let Some(program) = words.next() else {
+        return Ok(false);
+    };
+    debug!("Showing output in pager: {}", pager);
+
+    let mut child = match std::process::Command::new(program)
+        .args(words)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            debug!("Failed to start pager {}: {}", pager, err);
+            return Ok(false);
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may be closed before reading everything
+        match stdin.write_all(content) {
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => {}
+            result => result.context("Failed to write to the pager")?,
+        }
+    }
+    child.wait().context("Failed to wait for the pager")?;
+    Ok(true)
+}
+
+#[test]
+fn test_render() {
+    let mut writer = TableWriter {
+        writer: None,
+        style: TableStyle::Box,
+        header: vec![],
+        rows: vec![],
+        numeric: vec![],
+        max_width: 6,
+        null: String::new(),
+        blob_format: BlobFormat::Hex,
+        without_header: false,
+        use_pager: false,
+        encoding: encoding_rs::UTF_8,
+    };
+    writer
+        .write_header(&["id".to_string(), "name".to_string()], &[None, None])
+        .unwrap();
+    writer
+        .write_row(&[
+            SqliteValue::Integer(7),
+            SqliteValue::Text("東京都港区".to_string()),
+        ])
+        .unwrap();
+    writer
+        .write_row(&[
+            SqliteValue::Real(12.5),
+            SqliteValue::Text("a|b".to_string()),
+        ])
+        .unwrap();
+    assert_eq!(
+        writer.render(),
+        "┌──────┬───────┐\n\
+         │   id │ name  │\n\
+         ├──────┼───────┤\n\
+         │    7 │ 東京… │\n\
+         │ 12.5 │ a|b   │\n\
+         └──────┴───────┘\n"
+    );
+
+    writer.style = TableStyle::Markdown;
+    writer.rows[1][1] = writer.cell_text("a|b");
+    assert_eq!(
+        writer.render(),
+        "|   id | name  |\n\
+         |-----:|-------|\n\
+         |    7 | 東京… |\n\
+         | 12.5 | a\\|b  |\n"
+    );
+}
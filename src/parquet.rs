@@ -0,0 +1,2 @@
+pub mod parquet_parser;
+pub mod parquet_writer;
@@ -0,0 +1,290 @@
+use crate::args_util::args::Args;
+use crate::args_util::input_file::InputFile;
+use crate::db::detect_options::DetectOptions;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::table_loader::TableLoader;
+use crate::db::table_schema::TableSchema;
+use crate::format::STYLE_BAR;
+use crate::input::input_stream::open_seekable_input;
+use anyhow::Context;
+use arrow_array::{Array, RecordBatchReader};
+use arrow_schema::DataType;
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use tracing::debug;
+
+/// Number of rows decoded at a time.
+const BATCH_SIZE: usize = 8192;
+
+/// Parses Apache Parquet files and imports them into the SQLite database.
+///
+/// Column types follow the Parquet schema instead of being detected from the
+/// values, and row groups are decoded in batches, so only one batch is kept in
+/// memory at a time.
+#[derive(Debug)]
+pub struct ParquetParser<'a> {
+    /// Reference to application arguments
+    args: &'a Args,
+}
+
+impl<'a> ParquetParser<'a> {
+    /// Creates a new `ParquetParser` instance
+    pub fn new(args: &'a Args) -> Self {
+        Self { args }
+    }
+
+    /// Parses a Parquet file and imports it as the given table.
+    ///
+    /// # Arguments
+    /// * `conn` - Connection to import into.
+    /// * `input_file` - The input file.
+    /// * `table_name` - Name of the table to create.
+    pub fn parse_file(
+        &self,
+        conn: &rusqlite::Connection,
+        input_file: &InputFile,
+        table_name: &str,
+    ) -> anyhow::Result<()> {
+        debug!("Parsing Parquet file: {}", input_file);
+
+        // First pass: collect column names and nullability
+        let mut schema =
+            TableSchema::new(self.detect_options(), self.args.in_schema.table(table_name));
+        self.scan_file(input_file, &mut schema)?;
+        schema.finish(table_name)?;
+
+        // Second pass: create the table and insert rows
+        let loader = TableLoader::new(conn, table_name, &self.args.raw_id, &schema)?;
+        let row_count = self.load_file(input_file, &schema, &loader, None)?;
+        loader.finish()?;
+
+        debug!(
+            "Finished loading {} rows into table: {}",
+            row_count, table_name
+        );
+        Ok(())
+    }
+
+    /// Scans a file into a schema shared by several inputs.
+    pub fn scan_file(
+        &self,
+        input_file: &InputFile,
+        schema: &mut TableSchema,
+    ) -> anyhow::Result<()> {
+        let (reader, pb) = self.open_reader(input_file)?;
+        pb.set_message(format!("Scanning Parquet: {}", input_file));
+
+        let mut column_indexes: Vec<usize> = vec![];
+        for field in reader.schema().fields() {
+            let column_index = schema.column_index(field.name());
+            if column_indexes.contains(&column_index) {
+                return Err(anyhow::anyhow!(
+                    "Duplicate column name \"{}\" in {}",
+                    field.name(),
+                    input_file
+                ));
+            }
+            column_indexes.push(column_index);
+        }
+
+        let mut row_count: usize = 0;
+        for batch in reader {
+            let batch = batch.with_context(|| format!("Failed to read {}", input_file))?;
+            for (column, column_index) in batch.columns().iter().zip(&column_indexes) {
+                let data_type = column_type(column.data_type(), schema.options.detect_boolean);
+                for (row_offset, value) in column_values(column)?.iter().enumerate() {
+                    let result = match value {
+                        Some(value) => schema.observe_typed(*column_index, value, data_type),
+                        None => schema.observe(*column_index, None),
+                    };
+                    result.with_context(|| {
+                        format!(
+                            "Invalid data in row {} of {}",
+                            row_count + row_offset + 1,
+                            input_file
+                        )
+                    })?;
+                }
+            }
+            for _ in 0..batch.num_rows() {
+                schema.end_row();
+            }
+            row_count += batch.num_rows();
+            pb.inc(batch.num_rows() as u64);
+        }
+
+        pb.finish_and_clear();
+        Ok(())
+    }
+
+    /// Inserts the rows of a file scanned by `scan_file`.
+    ///
+    /// # Arguments
+    /// * `source` - Column index and value filled in for every row, e.g. the file name.
+    ///
+    /// # Returns
+    /// The number of rows inserted.
+    pub fn load_file(
+        &self,
+        input_file: &InputFile,
+        schema: &TableSchema,
+        loader: &TableLoader,
+        source: Option<(usize, &str)>,
+    ) -> anyhow::Result<usize> {
+        let (reader, pb) = self.open_reader(input_file)?;
+        pb.set_message(format!("Importing data from {}", input_file));
+
+        let column_indexes: Vec<Option<usize>> = reader
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| schema.position(field.name()))
+            .collect();
+
+        let mut row_count: usize = 0;
+        for batch in reader {
+            let batch = batch.with_context(|| format!("Failed to read {}", input_file))?;
+            let columns: Vec<Vec<Option<String>>> = batch
+                .columns()
+                .iter()
+                .map(|column| column_values(column))
+                .collect::<anyhow::Result<_>>()?;
+
+            for row_offset in 0..batch.num_rows() {
+                let mut values: Vec<Option<&str>> = vec![None; schema.headers.len()];
+                for (column, column_index) in columns.iter().zip(&column_indexes) {
+                    if let Some(column_index) = column_index {
+                        values[*column_index] = column[row_offset].as_deref();
+                    }
+                }
+                if let Some((column_index, value)) = source {
+                    values[column_index] = Some(value);
+                }
+                loader.insert(&values)?;
+            }
+            row_count += batch.num_rows();
+            pb.inc(batch.num_rows() as u64);
+        }
+
+        pb.finish_and_clear();
+        Ok(row_count)
+    }
+
+    /// Returns the settings used for Parquet values.
+    ///
+    /// Values are converted to plain numbers and ISO 8601 dates, so the number
+    /// locale and date formats given for text inputs do not apply.
+    fn detect_options(&self) -> DetectOptions {
+        DetectOptions {
+            number_locale: None,
+            date_formats: vec![],
+            ..DetectOptions::from(self.args)
+        }
+    }
+
+    /// Opens a reader returning the rows in batches, with a progress bar of the row count.
+    ///
+    /// Compressed files and archive members are extracted to a temporary file
+    /// first, since Parquet metadata is at the end of the file.
+    fn open_reader(
+        &self,
+        input_file: &InputFile,
+    ) -> anyhow::Result<(ParquetRecordBatchReader, indicatif::ProgressBar)> {
+        let file = open_seekable_input(input_file)?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+            .with_context(|| format!("Failed to read Parquet file {}", input_file))?;
+
+        let pb = indicatif::ProgressBar::new(builder.metadata().file_metadata().num_rows() as u64);
+        pb.set_style(STYLE_BAR.clone());
+
+        Ok((builder.with_batch_size(BATCH_SIZE).build()?, pb))
+    }
+}
+
+/// Returns the column type of a Parquet column, read as an Arrow type.
+///
+/// - Integers are `INTEGER`, floats and decimals with a fraction `REAL`.
+/// - Booleans are `BOOLEAN`, or `INTEGER` if `detect_boolean` is `false`.
+/// - Dates are `DATE`, timestamps `DATETIME` in UTC, and times `TIME`.
+/// - Strings, binary values in hex, and nested values are `TEXT`.
+fn column_type(data_type: &DataType, detect_boolean: bool) -> SqliteDataType {
+    match data_type {
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => SqliteDataType::Integer,
+        DataType::Decimal128(_, scale) | DataType::Decimal256(_, scale) if *scale <= 0 => {
+            SqliteDataType::Integer
+        }
+        DataType::Float16
+        | DataType::Float32
+        | DataType::Float64
+        | DataType::Decimal128(_, _)
+        | DataType::Decimal256(_, _) => SqliteDataType::Real,
+        DataType::Boolean if detect_boolean => SqliteDataType::Boolean,
+        DataType::Boolean => SqliteDataType::Integer,
+        DataType::Date32 | DataType::Date64 => SqliteDataType::Date,
+        DataType::Timestamp(_, _) => SqliteDataType::Datetime,
+        DataType::Time32(_) | DataType::Time64(_) => SqliteDataType::Time,
+        DataType::Dictionary(_, value_type) => column_type(value_type, detect_boolean),
+        _ => SqliteDataType::Text,
+    }
+}
+
+/// Converts the values of a column to text in a form its column type parses.
+///
+/// # Returns
+/// One value per row; `None` is `NULL`.
+fn column_values(column: &dyn Array) -> anyhow::Result<Vec<Option<String>>> {
+    if let Some(booleans) = column.as_any().downcast_ref::<arrow_array::BooleanArray>() {
+        return Ok(booleans
+            .iter()
+            .map(|value| value.map(|value| (value as i64).to_string()))
+            .collect());
+    }
+
+    let options = arrow_cast::display::FormatOptions::new()
+        .with_date_format(Some("%Y-%m-%d"))
+        .with_datetime_format(Some("%Y-%m-%d %H:%M:%S%.f"))
+        .with_timestamp_format(Some("%Y-%m-%d %H:%M:%S%.f"))
+        .with_timestamp_tz_format(Some("%Y-%m-%d %H:%M:%S%.f%:z"))
+        .with_time_format(Some("%H:%M:%S%.f"));
+    let formatter = arrow_cast::display::ArrayFormatter::try_new(column, &options)?;
+    Ok((0..column.len())
+        .map(|index| (!column.is_null(index)).then(|| formatter.value(index).to_string()))
+        .collect())
+}
+
+#[test]
+fn test_column_values() {
+    use std::sync::Arc;
+
+    let timestamps =
+        arrow_array::TimestampMillisecondArray::from(vec![Some(1_709_287_200_500), None])
+            .with_timezone("+09:00");
+    assert_eq!(
+        column_values(&timestamps).unwrap(),
+        vec![Some("2024-03-01 19:00:00.500+09:00".to_string()), None]
+    );
+    assert_eq!(
+        column_type(timestamps.data_type(), true),
+        SqliteDataType::Datetime
+    );
+
+    let booleans: Arc<dyn Array> = Arc::new(arrow_array::BooleanArray::from(vec![true, false]));
+    assert_eq!(
+        column_values(&booleans).unwrap(),
+        vec![Some("1".to_string()), Some("0".to_string())]
+    );
+    assert_eq!(
+        column_type(&DataType::Decimal128(10, 2), true),
+        SqliteDataType::Real
+    );
+    assert_eq!(
+        column_type(&DataType::Decimal128(10, 0), true),
+        SqliteDataType::Integer
+    );
+}
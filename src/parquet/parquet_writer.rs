@@ -0,0 +1,329 @@
+use crate::args_util::args::Args;
+use crate::args_util::blob_format::BlobFormat;
+use crate::db::inferred_type::InferredType;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::sqlite_value::SqliteValue;
+use crate::db::temporal_value::TemporalValue;
+use crate::output::output_stream::{OutputStream, open_binary_output_stream};
+use crate::output::result_writer::ResultWriter;
+use anyhow::Context;
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, Float64Array, Int64Array, RecordBatch,
+    StringArray, Time64MicrosecondArray, TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{NaiveDate, Timelike};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Number of rows converted into one record batch.
+const BATCH_SIZE: usize = 8192;
+
+/// Writes query results as an Apache Parquet file compressed with Snappy.
+///
+/// Column types come from the declared types of the result: `INTEGER`, `REAL`,
+/// `TEXT`, `BOOLEAN`, `DATE`, `DATETIME` and `TIME` columns are written as the
+/// matching Parquet types. Columns without a declared type, such as expressions,
+/// take the type of their values in the first batch of rows, with numbers always
+/// written as `Float64`.
+pub struct ParquetWriter {
+    /// Output destination; `None` after `finish`.
+    writer: Option<OutputStream>,
+    /// Encoder of the file; created with the first batch, once column types are known.
+    arrow_writer: Option<ArrowWriter<Vec<u8>>>,
+    /// Schema of the file, set with the encoder.
+    schema: SchemaRef,
+    /// Column names.
+    header: Vec<String>,
+    /// Logical type of each column, from the declared types.
+    column_types: Vec<Option<SqliteDataType>>,
+    /// Rows not converted into a batch yet.
+    rows: Vec<Vec<SqliteValue>>,
+    /// Number of rows written in previous batches.
+    row_count: usize,
+    /// Text encoding for BLOB values in text columns.
+    blob_format: BlobFormat,
+}
+
+impl ParquetWriter {
+    /// Creates a new ParquetWriter based on the provided arguments.
+    pub fn new(args: &Args) -> anyhow::Result<Self> {
+        debug!("Initializing ParquetWriter.");
+        Ok(Self {
+            writer: Some(open_binary_output_stream(args)?),
+            arrow_writer: None,
+            schema: Arc::new(Schema::empty()),
+            header: vec![],
+            column_types: vec![],
+            rows: Vec::with_capacity(BATCH_SIZE),
+            row_count: 0,
+            blob_format: args.out_blob_format,
+        })
+    }
+
+    /// Converts the buffered rows into a batch, encodes it, and writes the completed bytes.
+    fn write_batch(&mut self) -> anyhow::Result<()> {
+        if self.arrow_writer.is_none() {
+            let properties = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build();
+            self.schema = Arc::new(self.schema());
+            self.arrow_writer = Some(ArrowWriter::try_new(
+                Vec::new(),
+                self.schema.clone(),
+                Some(properties),
+            )?);
+        }
+        let Some(arrow_writer) = &mut self.arrow_writer else {
+            return Ok(());
+        };
+
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(column_index, field)| {
+                column_array(
+                    &self.rows,
+                    column_index,
+                    field.data_type(),
+                    self.blob_format,
+                )
+                .map_err(|(row_offset, value)| {
+                    anyhow::anyhow!(
+                        "Cannot write {} in row {} to the {} column \"{}\". Use CAST in the query to convert it.",
+                        value,
+                        self.row_count + row_offset + 1,
+                        field.data_type(),
+                        field.name()
+                    )
+                })
+            })
+            .collect::<anyhow::Result<Vec<ArrayRef>>>()?;
+        arrow_writer.write(&RecordBatch::try_new(self.schema.clone(), columns)?)?;
+
+        self.row_count += self.rows.len();
+        self.rows.clear();
+
+        // Row groups are completed in the buffer; pass them on as they come
+        let content = std::mem::take(arrow_writer.inner_mut());
+        if let Some(writer) = &mut self.writer {
+            writer.write_all(&content)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the Arrow schema from the declared types and the buffered rows.
+    fn schema(&self) -> Schema {
+        let fields: Vec<Field> = self
+            .header
+            .iter()
+            .enumerate()
+            .map(|(column_index, name)| {
+                let data_type = match self.column_types.get(column_index).copied().flatten() {
+                    Some(data_type) => arrow_type(data_type),
+                    None => inferred_type(&self.rows, column_index),
+                };
+                Field::new(name, data_type, true)
+            })
+            .collect();
+        Schema::new(fields)
+    }
+}
+
+impl ResultWriter for ParquetWriter {
+    /// Keeps the column names and types for the schema, written with the first batch.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        self.header = header.to_vec();
+        self.column_types = declared_types
+            .iter()
+            .map(|declared_type| {
+                declared_type
+                    .as_deref()
+                    .and_then(SqliteDataType::from_declared_type)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Buffers a row, and writes a batch when enough rows are buffered.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        self.rows.push(row.to_vec());
+        if self.rows.len() >= BATCH_SIZE {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the remaining rows and the file footer, then completes compressed output.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if self.writer.is_none() {
+            return Ok(());
+        }
+        if !self.rows.is_empty() || self.arrow_writer.is_none() {
+            self.write_batch()?;
+        }
+        if let (Some(mut writer), Some(arrow_writer)) =
+            (self.writer.take(), self.arrow_writer.take())
+        {
+            let content = arrow_writer
+                .into_inner()
+                .context("Failed to complete the Parquet file")?;
+            writer.write_all(&content)?;
+            writer.finish().context("Failed to finish output")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the Parquet column type of a declared column type.
+fn arrow_type(data_type: SqliteDataType) -> DataType {
+    match data_type {
+        SqliteDataType::Integer => DataType::Int64,
+        SqliteDataType::Real => DataType::Float64,
+        SqliteDataType::Boolean => DataType::Boolean,
+        SqliteDataType::Date => DataType::Date32,
+        SqliteDataType::Datetime => DataType::Timestamp(TimeUnit::Microsecond, None),
+        SqliteDataType::Time => DataType::Time64(TimeUnit::Microsecond),
+        _ => DataType::Utf8,
+    }
+}
+
+/// Returns the column type of a column without a declared type from its values.
+///
+/// Numbers are `Float64`, so a `REAL` in a later batch still fits, and BLOBs
+/// `Binary`. Anything else, including a column of only `NULL`, is text.
+fn inferred_type(rows: &[Vec<SqliteValue>], column_index: usize) -> DataType {
+    match InferredType::infer(rows.iter().filter_map(|row| row.get(column_index))) {
+        Some(InferredType::Numeric) => DataType::Float64,
+        Some(InferredType::Blob) => DataType::Binary,
+        Some(InferredType::Text) | None => DataType::Utf8,
+    }
+}
+
+/// Converts a column of the buffered rows into an Arrow array of the given type.
+///
+/// # Returns
+/// The array, or the row offset and text of the first value that does not fit the type.
+fn column_array(
+    rows: &[Vec<SqliteValue>],
+    column_index: usize,
+    data_type: &DataType,
+    blob_format: BlobFormat,
+) -> Result<ArrayRef, (usize, String)> {
+    let values = rows
+        .iter()
+        .map(|row| row.get(column_index).unwrap_or(&SqliteValue::Null));
+
+    /// Converts every non-NULL value with `convert`, stopping at the first that fails.
+    fn collect<'a, T>(
+        values: impl Iterator<Item = &'a SqliteValue>,
+        convert: impl Fn(&SqliteValue) -> Option<T>,
+    ) -> Result<Vec<Option<T>>, (usize, String)> {
+        values
+            .enumerate()
+            .map(|(row_offset, value)| match value {
+                SqliteValue::Null => Ok(None),
+                value => convert(value)
+                    .map(Some)
+                    .ok_or_else(|| (row_offset, format!("{:?}", value.to_string()))),
+            })
+            .collect()
+    }
+
+    let array: ArrayRef = match data_type {
+        DataType::Int64 => Arc::new(Int64Array::from(collect(values, |value| match value {
+            SqliteValue::Integer(number) => Some(*number),
+            _ => None,
+        })?)),
+        DataType::Float64 => Arc::new(Float64Array::from(collect(values, |value| match value {
+            SqliteValue::Integer(number) => Some(*number as f64),
+            SqliteValue::Real(number) => Some(*number),
+            _ => None,
+        })?)),
+        DataType::Boolean => Arc::new(BooleanArray::from(collect(values, |value| match value {
+            SqliteValue::Integer(number @ (0 | 1)) => Some(*number == 1),
+            _ => None,
+        })?)),
+        DataType::Date32 => {
+            Arc::new(Date32Array::from(collect(
+                values,
+                |value| match TemporalValue::from_stored(value)? {
+                    TemporalValue::Date(date) => {
+                        Some((date - NaiveDate::from_ymd_opt(1970, 1, 1)?).num_days() as i32)
+                    }
+                    _ => None,
+                },
+            )?))
+        }
+        DataType::Timestamp(_, _) => {
+            Arc::new(TimestampMicrosecondArray::from(collect(values, |value| {
+                match TemporalValue::from_stored(value)?.into_datetime() {
+                    TemporalValue::Datetime(datetime) => {
+                        Some(datetime.and_utc().timestamp_micros())
+                    }
+                    _ => None,
+                }
+            })?))
+        }
+        DataType::Time64(_) => Arc::new(Time64MicrosecondArray::from(collect(values, |value| {
+            match TemporalValue::from_stored(value)? {
+                TemporalValue::Time(time) => Some(
+                    time.num_seconds_from_midnight() as i64 * 1_000_000
+                        + time.nanosecond() as i64 / 1_000,
+                ),
+                _ => None,
+            }
+        })?)),
+        DataType::Binary => {
+            let values: Vec<Option<Vec<u8>>> = collect(values, |value| match value {
+                SqliteValue::Blob(bytes) => Some(bytes.clone()),
+                other => Some(other.to_string().into_bytes()),
+            })?;
+            Arc::new(BinaryArray::from_iter(values))
+        }
+        _ => Arc::new(StringArray::from(collect(values, |value| {
+            Some(value.to_text(blob_format))
+        })?)),
+    };
+    Ok(array)
+}
+
+#[test]
+fn test_column_array() {
+    let rows = vec![
+        vec![SqliteValue::Text("2024-03-01 18:00:00".to_string())],
+        vec![SqliteValue::Integer(1_709_316_000)],
+        vec![SqliteValue::Null],
+    ];
+    let array = column_array(
+        &rows,
+        0,
+        &DataType::Timestamp(TimeUnit::Microsecond, None),
+        BlobFormat::Hex,
+    )
+    .unwrap();
+    let timestamps = array
+        .as_any()
+        .downcast_ref::<TimestampMicrosecondArray>()
+        .unwrap();
+    assert_eq!(timestamps.value(0), 1_709_316_000_000_000);
+    assert_eq!(timestamps.value(1), 1_709_316_000_000_000);
+    assert!(arrow_array::Array::is_null(timestamps, 2));
+
+    let rows = vec![vec![SqliteValue::Integer(1)], vec![SqliteValue::Real(2.5)]];
+    assert_eq!(inferred_type(&rows[..1], 0), DataType::Float64);
+    assert_eq!(
+        column_array(&rows, 0, &DataType::Int64, BlobFormat::Hex).unwrap_err(),
+        (1, "\"2.5\"".to_string())
+    );
+}
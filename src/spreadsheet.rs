@@ -0,0 +1,2 @@
+pub mod spreadsheet_parser;
+pub mod xlsx_writer;
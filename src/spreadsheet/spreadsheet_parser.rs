@@ -0,0 +1,390 @@
+use crate::args_util::args::Args;
+use crate::args_util::input_file::InputFile;
+use crate::db::detect_options::DetectOptions;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::table_loader::TableLoader;
+use crate::db::table_schema::TableSchema;
+use crate::db::temporal_value::TemporalValue;
+use crate::format::STYLE_BAR;
+use anyhow::Context;
+use calamine::{Data, Reader};
+use tracing::debug;
+
+/// Parses sheets of Excel and OpenDocument workbooks and imports them into the SQLite database.
+///
+/// Each sheet becomes a table. Numbers, booleans and dates keep the type of
+/// their cells instead of being detected from text, and text cells stay text
+/// even if they look like numbers. Blank rows are skipped.
+#[derive(Debug)]
+pub struct SpreadsheetParser<'a> {
+    /// Reference to application arguments
+    args: &'a Args,
+}
+
+/// Cells of a sheet within `--in-range`, with the column names.
+struct Sheet {
+    /// Column names from the header row, or `c1`, `c2`, ... without one.
+    headers: Vec<String>,
+    /// Cells of the sheet.
+    range: calamine::Range<Data>,
+    /// Index of the first data row in `range`.
+    first_row: usize,
+}
+
+impl Sheet {
+    /// Returns the data rows that have at least one cell, with their row number in the sheet.
+    fn rows(&self) -> impl Iterator<Item = (u32, &[Data])> {
+        let start_row = self.range.start().map_or(0, |(row, _)| row);
+        self.range
+            .rows()
+            .enumerate()
+            .skip(self.first_row)
+            .filter(|(_, row)| row.iter().any(|cell| !matches!(cell, Data::Empty)))
+            .map(move |(index, row)| (start_row + index as u32 + 1, row))
+    }
+}
+
+impl<'a> SpreadsheetParser<'a> {
+    /// Creates a new `SpreadsheetParser` instance
+    pub fn new(args: &'a Args) -> Self {
+        Self { args }
+    }
+
+    /// Parses a sheet of a workbook and imports it as the given table.
+    ///
+    /// A sheet without cells is skipped and no table is created.
+    ///
+    /// # Arguments
+    /// * `conn` - Connection to import into.
+    /// * `input_file` - The sheet, as `report.xlsx#Sheet1`.
+    /// * `table_name` - Name of the table to create.
+    pub fn parse_file(
+        &self,
+        conn: &rusqlite::Connection,
+        input_file: &InputFile,
+        table_name: &str,
+    ) -> anyhow::Result<()> {
+        debug!("Parsing sheet: {}", input_file);
+
+        // First pass: collect column names and merge the cell types
+        let mut schema =
+            TableSchema::new(self.detect_options(), self.args.in_schema.table(table_name));
+        self.scan_file(input_file, &mut schema)?;
+        if schema.headers.is_empty() {
+            debug!("Skipped empty sheet: {}", input_file);
+            return Ok(());
+        }
+        schema.finish(table_name)?;
+
+        // Second pass: create the table and insert rows
+        let loader = TableLoader::new(conn, table_name, &self.args.raw_id, &schema)?;
+        let row_count = self.load_file(input_file, &schema, &loader, None)?;
+        loader.finish()?;
+
+        debug!(
+            "Finished loading {} rows into table: {}",
+            row_count, table_name
+        );
+        Ok(())
+    }
+
+    /// Scans a sheet into a schema shared by several inputs.
+    pub fn scan_file(
+        &self,
+        input_file: &InputFile,
+        schema: &mut TableSchema,
+    ) -> anyhow::Result<()> {
+        let sheet = self.read_sheet(input_file)?;
+        let pb = Self::progress_bar(&sheet);
+        pb.set_message(format!("Scanning sheet: {}", input_file));
+
+        let mut column_indexes: Vec<usize> = vec![];
+        for header in &sheet.headers {
+            let column_index = schema.column_index(header);
+            if column_indexes.contains(&column_index) {
+                return Err(anyhow::anyhow!(
+                    "Duplicate column name \"{}\" in {}",
+                    header,
+                    input_file
+                ));
+            }
+            column_indexes.push(column_index);
+        }
+
+        for (row_number, row) in sheet.rows() {
+            for (cell, column_index) in row.iter().zip(&column_indexes) {
+                let result = match self.cell_value(cell) {
+                    Some((value, data_type)) => {
+                        schema.observe_typed(*column_index, &value, data_type)
+                    }
+                    None => schema.observe(*column_index, None),
+                };
+                result.with_context(|| {
+                    format!("Invalid data in row {} of {}", row_number, input_file)
+                })?;
+            }
+            schema.end_row();
+            pb.inc(1);
+        }
+
+        pb.finish_and_clear();
+        Ok(())
+    }
+
+    /// Inserts the rows of a sheet scanned by `scan_file`.
+    ///
+    /// # Arguments
+    /// * `source` - Column index and value filled in for every row, e.g. the sheet name.
+    ///
+    /// # Returns
+    /// The number of rows inserted.
+    pub fn load_file(
+        &self,
+        input_file: &InputFile,
+        schema: &TableSchema,
+        loader: &TableLoader,
+        source: Option<(usize, &str)>,
+    ) -> anyhow::Result<usize> {
+        let sheet = self.read_sheet(input_file)?;
+        let pb = Self::progress_bar(&sheet);
+        pb.set_message(format!("Importing data from {}", input_file));
+
+        let column_indexes: Vec<Option<usize>> = sheet
+            .headers
+            .iter()
+            .map(|header| schema.position(header))
+            .collect();
+
+        let mut row_count: usize = 0;
+        for (_, row) in sheet.rows() {
+            let cells: Vec<Option<String>> = row
+                .iter()
+                .map(|cell| self.cell_value(cell).map(|(value, _)| value))
+                .collect();
+            let mut values: Vec<Option<&str>> = vec![None; schema.headers.len()];
+            for (cell, column_index) in cells.iter().zip(&column_indexes) {
+                if let Some(column_index) = column_index {
+                    values[*column_index] = cell.as_deref();
+                }
+            }
+            if let Some((column_index, value)) = source {
+                values[column_index] = Some(value);
+            }
+            loader.insert(&values)?;
+
+            row_count += 1;
+            pb.inc(1);
+        }
+
+        pb.finish_and_clear();
+        Ok(row_count)
+    }
+
+    /// Returns the settings used for cell values.
+    ///
+    /// Cells are converted to plain numbers and ISO 8601 dates, so the number
+    /// locale and date formats given for text inputs do not apply.
+    fn detect_options(&self) -> DetectOptions {
+        DetectOptions {
+            number_locale: None,
+            date_formats: vec![],
+            ..DetectOptions::from(self.args)
+        }
+    }
+
+    /// Creates a progress bar counting the rows of a sheet.
+    fn progress_bar(sheet: &Sheet) -> indicatif::ProgressBar {
+        let row_count = sheet.range.height().saturating_sub(sheet.first_row);
+        let pb = indicatif::ProgressBar::new(row_count as u64);
+        pb.set_style(STYLE_BAR.clone());
+        pb
+    }
+
+    /// Reads the cells of a sheet within `--in-range` and its header row.
+    ///
+    /// Rows above `--in-header-row` are skipped. Without it, the first row of the
+    /// range is the header row, unless `--in-no-header` is given.
+    fn read_sheet(&self, input_file: &InputFile) -> anyhow::Result<Sheet> {
+        let sheet_name = input_file
+            .sheet()
+            .with_context(|| format!("No sheet given for {}", input_file))?;
+        let mut workbook = calamine::open_workbook_auto(&input_file.path)
+            .with_context(|| format!("Failed to open workbook {}", input_file.path))?;
+        let mut range = workbook
+            .worksheet_range(sheet_name)
+            .with_context(|| format!("Failed to read sheet {}", input_file))?;
+
+        if let Some(cell_range) = self.args.in_range {
+            let (start_row, start_column) = cell_range.start;
+            let (end_row, end_column) = cell_range.end.or(range.end()).unwrap_or(cell_range.start);
+            range = range.range(
+                cell_range.start,
+                (end_row.max(start_row), end_column.max(start_column)),
+            );
+        }
+        if range.used_cells().next().is_none() {
+            return Ok(Sheet {
+                headers: vec![],
+                range,
+                first_row: 0,
+            });
+        }
+
+        let mut first_row = match (self.args.in_header_row, range.start()) {
+            (Some(header_row), Some((start_row, _))) => {
+                (header_row - 1).saturating_sub(start_row) as usize
+            }
+            _ => 0,
+        };
+        let headers = if self.args.in_no_header {
+            (1..=range.width())
+                .map(|index| format!("c{}", index))
+                .collect()
+        } else {
+            let header_row = range.rows().nth(first_row).unwrap_or_default();
+            first_row += 1;
+            (0..range.width())
+                .map(|index| self.header_name(header_row.get(index), index))
+                .collect()
+        };
+        Ok(Sheet {
+            headers,
+            range,
+            first_row,
+        })
+    }
+
+    /// Returns the column name of a header cell, or `c1`, `c2`, ... for an empty one.
+    fn header_name(&self, cell: Option<&Data>, index: usize) -> String {
+        let name = match cell {
+            Some(Data::String(name)) => match self.args.in_trim {
+                csv::Trim::Headers | csv::Trim::All => name.trim().to_string(),
+                _ => name.to_string(),
+            },
+            Some(cell) => cell_value(cell, true)
+                .map(|(value, _)| value)
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+        if name.is_empty() {
+            format!("c{}", index + 1)
+        } else {
+            name
+        }
+    }
+
+    /// Converts a cell applying `--in-trim` and `--in-null` to text cells.
+    ///
+    /// # Returns
+    /// The value and its type, or `None` for an empty cell.
+    fn cell_value(&self, cell: &Data) -> Option<(String, SqliteDataType)> {
+        match cell {
+            Data::String(text) => {
+                let text = match self.args.in_trim {
+                    csv::Trim::Fields | csv::Trim::All => text.trim(),
+                    _ => text,
+                };
+                if text.is_empty() || self.args.in_null.iter().any(|token| token == text) {
+                    None
+                } else {
+                    Some((text.to_string(), SqliteDataType::Text))
+                }
+            }
+            other => cell_value(other, self.args.in_detect_boolean),
+        }
+    }
+}
+
+/// Converts a cell to text in a form its type parses, with the type of the cell.
+///
+/// - Whole numbers are `INTEGER`, other numbers `REAL`, since workbooks store all numbers as floats.
+/// - Booleans are `1`/`0` as `BOOLEAN`, or as `INTEGER` if `detect_boolean` is `false`.
+/// - Dates are `DATE` at midnight, times below one day `TIME`, others `DATETIME`.
+/// - Durations such as `[h]:mm` and text cells are `TEXT`.
+/// - Error cells such as `#N/A` are empty.
+fn cell_value(cell: &Data, detect_boolean: bool) -> Option<(String, SqliteDataType)> {
+    // Largest float range where every whole number is exact
+    const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+    match cell {
+        Data::Empty | Data::Error(_) => None,
+        Data::String(text) => Some((text.to_string(), SqliteDataType::Text)),
+        Data::Int(number) => Some((number.to_string(), SqliteDataType::Integer)),
+        Data::Float(number) if number.fract() == 0.0 && number.abs() <= MAX_EXACT_INTEGER => {
+            Some(((*number as i64).to_string(), SqliteDataType::Integer))
+        }
+        Data::Float(number) => Some((number.to_string(), SqliteDataType::Real)),
+        Data::Bool(value) => {
+            let data_type = if detect_boolean {
+                SqliteDataType::Boolean
+            } else {
+                SqliteDataType::Integer
+            };
+            Some(((*value as i64).to_string(), data_type))
+        }
+        Data::DateTime(value) if value.is_duration() => {
+            let seconds = value.as_duration()?.num_seconds();
+            Some((
+                format!(
+                    "{}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds % 3600 / 60,
+                    seconds % 60
+                ),
+                SqliteDataType::Text,
+            ))
+        }
+        Data::DateTime(value) => {
+            let datetime = value.as_datetime()?;
+            if value.as_f64() < 1.0 {
+                Some((
+                    datetime.format("%H:%M:%S%.f").to_string(),
+                    SqliteDataType::Time,
+                ))
+            } else if datetime.time() == chrono::NaiveTime::MIN {
+                Some((
+                    datetime.format("%Y-%m-%d").to_string(),
+                    SqliteDataType::Date,
+                ))
+            } else {
+                Some((
+                    datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                    SqliteDataType::Datetime,
+                ))
+            }
+        }
+        Data::DateTimeIso(text) => {
+            let data_type = TemporalValue::parse(text, &[])
+                .map_or(SqliteDataType::Text, |value| value.data_type());
+            Some((text.to_string(), data_type))
+        }
+        Data::DurationIso(text) => Some((text.to_string(), SqliteDataType::Text)),
+    }
+}
+
+#[test]
+fn test_cell_value() {
+    let value = |cell: Data| cell_value(&cell, true);
+    assert_eq!(
+        value(Data::Float(42.0)),
+        Some(("42".to_string(), SqliteDataType::Integer))
+    );
+    assert_eq!(
+        value(Data::Float(-1.5)),
+        Some(("-1.5".to_string(), SqliteDataType::Real))
+    );
+    assert_eq!(
+        value(Data::Bool(true)),
+        Some(("1".to_string(), SqliteDataType::Boolean))
+    );
+    assert_eq!(
+        value(Data::String("007".to_string())),
+        Some(("007".to_string(), SqliteDataType::Text))
+    );
+    assert_eq!(
+        value(Data::DateTimeIso("2024-03-01T09:30:00".to_string())),
+        Some(("2024-03-01T09:30:00".to_string(), SqliteDataType::Datetime))
+    );
+    assert_eq!(value(Data::Error(calamine::CellErrorType::NA)), None);
+    assert_eq!(value(Data::Empty), None);
+}
@@ -0,0 +1,312 @@
+use crate::args_util::args::Args;
+use crate::args_util::blob_format::BlobFormat;
+use crate::db::sqlite_data_type::SqliteDataType;
+use crate::db::sqlite_value::SqliteValue;
+use crate::db::temporal_value::TemporalValue;
+use crate::output::output_stream::{OutputStream, open_binary_output_stream};
+use crate::output::result_writer::ResultWriter;
+use anyhow::Context;
+use chrono::{Datelike, Timelike};
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet};
+use std::io::Write;
+use tracing::debug;
+
+/// Largest integer written as a number; Excel stores numbers as floats.
+const MAX_EXACT_INTEGER: i64 = 9_007_199_254_740_992;
+
+/// Writes query results as an Excel workbook.
+///
+/// The header row is bold and frozen, and columns are sized to their content.
+/// Numbers and booleans are written as such, and values of `DATE`, `DATETIME`
+/// and `TIME` columns as date cells. The workbook is built in memory and
+/// written by `finish`. With `--out-all-results`, each result gets its own worksheet.
+pub struct XlsxWriter {
+    /// Output destination; `None` after `finish`.
+    writer: Option<OutputStream>,
+    /// Workbook holding the worksheet when finished.
+    workbook: Workbook,
+    /// Worksheet of the current result.
+    worksheet: Worksheet,
+    /// Set once a header is written, so the next one starts another worksheet.
+    has_result: bool,
+    /// Index of the next row to write.
+    row_index: u32,
+    /// Logical type of each column, from the declared types.
+    column_types: Vec<Option<SqliteDataType>>,
+    /// Skips the header row when `true`.
+    without_header: bool,
+    /// Text encoding for BLOB values.
+    blob_format: BlobFormat,
+    /// Formats of the header row and of date cells.
+    formats: XlsxFormats,
+}
+
+/// Cell formats used by `XlsxWriter`.
+struct XlsxFormats {
+    header: Format,
+    date: Format,
+    datetime: Format,
+    time: Format,
+}
+
+impl XlsxWriter {
+    /// Creates a new XlsxWriter based on the provided arguments.
+    pub fn new(args: &Args) -> anyhow::Result<Self> {
+        debug!("Initializing XlsxWriter.");
+        Ok(Self {
+            writer: Some(open_binary_output_stream(args)?),
+            workbook: Workbook::new(),
+            worksheet: Worksheet::new(),
+            has_result: false,
+            row_index: 0,
+            column_types: vec![],
+            without_header: args.out_without_header,
+            blob_format: args.out_blob_format,
+            formats: XlsxFormats {
+                header: Format::new().set_bold(),
+                date: Format::new().set_num_format("yyyy-mm-dd"),
+                datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+                time: Format::new().set_num_format("hh:mm:ss"),
+            },
+        })
+    }
+
+    /// Writes a single value, keeping numbers, booleans and dates typed.
+    ///
+    /// Values that do not fit a cell type, such as dates before 1900, are written as text.
+    fn write_value(
+        &mut self,
+        column_index: u16,
+        value: &SqliteValue,
+        data_type: Option<SqliteDataType>,
+    ) -> anyhow::Result<()> {
+        let row_index = self.row_index;
+        match value {
+            SqliteValue::Null => {}
+            SqliteValue::Integer(number @ (0 | 1))
+                if data_type == Some(SqliteDataType::Boolean) =>
+            {
+                self.worksheet
+                    .write_boolean(row_index, column_index, *number == 1)?;
+            }
+            SqliteValue::Integer(number) if data_type.is_some_and(is_temporal) => {
+                self.write_timestamp(column_index, *number as f64, data_type, value)?;
+            }
+            SqliteValue::Real(number) if data_type.is_some_and(is_temporal) => {
+                self.write_timestamp(column_index, *number, data_type, value)?;
+            }
+            SqliteValue::Integer(number) if number.unsigned_abs() <= MAX_EXACT_INTEGER as u64 => {
+                self.worksheet
+                    .write_number(row_index, column_index, *number as f64)?;
+            }
+            SqliteValue::Real(number) if number.is_finite() => {
+                self.worksheet
+                    .write_number(row_index, column_index, *number)?;
+            }
+            SqliteValue::Text(text) if data_type.is_some_and(is_temporal) => {
+                match TemporalValue::parse(text, &[]).and_then(excel_datetime) {
+                    Some((datetime, data_type)) => {
+                        let format = self.formats.of(data_type);
+                        self.worksheet.write_datetime_with_format(
+                            row_index,
+                            column_index,
+                            &datetime,
+                            format,
+                        )?;
+                    }
+                    None => {
+                        self.worksheet.write_string(row_index, column_index, text)?;
+                    }
+                }
+            }
+            other => {
+                self.worksheet.write_string(
+                    row_index,
+                    column_index,
+                    other.to_text(self.blob_format),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a date stored as Unix time with `--in-date-storage epoch`.
+    fn write_timestamp(
+        &mut self,
+        column_index: u16,
+        seconds: f64,
+        data_type: Option<SqliteDataType>,
+        value: &SqliteValue,
+    ) -> anyhow::Result<()> {
+        // Days since the Excel epoch, 1899-12-30
+        let serial = seconds / 86_400.0 + 25_569.0;
+        match ExcelDateTime::from_serial_datetime(serial) {
+            Ok(datetime) => {
+                let format = self
+                    .formats
+                    .of(data_type.unwrap_or(SqliteDataType::Datetime));
+                self.worksheet.write_datetime_with_format(
+                    self.row_index,
+                    column_index,
+                    &datetime,
+                    format,
+                )?;
+            }
+            Err(_) => {
+                self.worksheet.write_string(
+                    self.row_index,
+                    column_index,
+                    value.to_text(self.blob_format),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl XlsxFormats {
+    /// Returns the number format of a date, datetime or time cell.
+    fn of(&self, data_type: SqliteDataType) -> &Format {
+        match data_type {
+            SqliteDataType::Date => &self.date,
+            SqliteDataType::Time => &self.time,
+            _ => &self.datetime,
+        }
+    }
+}
+
+impl ResultWriter for XlsxWriter {
+    /// Writes the bold header row and freezes it, unless `--out-without-header` is given.
+    ///
+    /// A header after the rows of a previous result starts a new worksheet.
+    fn write_header(
+        &mut self,
+        header: &[String],
+        declared_types: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        if self.has_result {
+            self.worksheet.autofit();
+            self.workbook
+                .push_worksheet(std::mem::replace(&mut self.worksheet, Worksheet::new()));
+            self.row_index = 0;
+        }
+        self.has_result = true;
+        self.column_types = declared_types
+            .iter()
+            .map(|declared_type| {
+                declared_type
+                    .as_deref()
+                    .and_then(SqliteDataType::from_declared_type)
+            })
+            .collect();
+
+        if !self.without_header {
+            for (column_index, name) in header.iter().enumerate() {
+                self.worksheet.write_string_with_format(
+                    0,
+                    column_number(column_index)?,
+                    name,
+                    &self.formats.header,
+                )?;
+            }
+            self.worksheet.set_freeze_panes(1, 0)?;
+            self.row_index = 1;
+        }
+        Ok(())
+    }
+
+    /// Writes a single row of typed cells.
+    fn write_row(&mut self, row: &[SqliteValue]) -> anyhow::Result<()> {
+        for (column_index, value) in row.iter().enumerate() {
+            let data_type = self.column_types.get(column_index).copied().flatten();
+            self.write_value(column_number(column_index)?, value, data_type)
+                .with_context(|| format!("Failed to write row {}", self.row_index + 1))?;
+        }
+        self.row_index += 1;
+        Ok(())
+    }
+
+    /// Sizes the columns, then writes the workbook and completes compressed output.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            self.worksheet.autofit();
+            self.workbook
+                .push_worksheet(std::mem::replace(&mut self.worksheet, Worksheet::new()));
+            let content = self
+                .workbook
+                .save_to_buffer()
+                .context("Failed to create the workbook")?;
+            writer.write_all(&content)?;
+            writer.finish().context("Failed to finish output")?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts a column index to a worksheet column number.
+fn column_number(column_index: usize) -> anyhow::Result<u16> {
+    u16::try_from(column_index)
+        .map_err(|_| anyhow::anyhow!("Too many columns for a worksheet: {}", column_index + 1))
+}
+
+/// Returns `true` for types written as date cells.
+fn is_temporal(data_type: SqliteDataType) -> bool {
+    matches!(
+        data_type,
+        SqliteDataType::Date | SqliteDataType::Datetime | SqliteDataType::Time
+    )
+}
+
+/// Converts a date, datetime or time to an Excel date with its type.
+///
+/// # Returns
+/// `None` for dates outside the years 1900 to 9999 supported by Excel.
+fn excel_datetime(value: TemporalValue) -> Option<(ExcelDateTime, SqliteDataType)> {
+    let datetime = match value {
+        TemporalValue::Date(date) => {
+            ExcelDateTime::from_ymd(date.year() as u16, date.month() as u8, date.day() as u8)
+        }
+        TemporalValue::Datetime(datetime) => ExcelDateTime::from_ymd(
+            datetime.year() as u16,
+            datetime.month() as u8,
+            datetime.day() as u8,
+        )
+        .and_then(|date| {
+            date.and_hms(
+                datetime.hour() as u16,
+                datetime.minute() as u8,
+                seconds(datetime.time()),
+            )
+        }),
+        TemporalValue::Time(time) => {
+            ExcelDateTime::from_hms(time.hour() as u16, time.minute() as u8, seconds(time))
+        }
+    };
+    datetime.ok().map(|datetime| (datetime, value.data_type()))
+}
+
+/// Returns the seconds of a time with the fraction.
+fn seconds(time: chrono::NaiveTime) -> f64 {
+    time.second() as f64 + time.nanosecond() as f64 / 1e9
+}
+
+#[test]
+fn test_excel_datetime() {
+    let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let (datetime, data_type) = excel_datetime(TemporalValue::Date(date)).unwrap();
+    assert_eq!(datetime.to_excel(), 45352.0);
+    assert_eq!(data_type, SqliteDataType::Date);
+
+    let time = chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+    let (datetime, _) = excel_datetime(TemporalValue::Datetime(date.and_time(time))).unwrap();
+    assert_eq!(datetime.to_excel(), 45352.75);
+
+    let date = chrono::NaiveDate::from_ymd_opt(1850, 1, 1).unwrap();
+    assert!(excel_datetime(TemporalValue::Date(date)).is_none());
+}
+
+#[test]
+fn test_column_number() {
+    assert_eq!(column_number(3).unwrap(), 3);
+    assert!(column_number(usize::from(u16::MAX) + 1).is_err());
+}
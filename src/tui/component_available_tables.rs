@@ -56,12 +56,11 @@ impl Component<TuiMsg, TuiUserEvent> for ComponentAvailableTables {
             Event::Keyboard(KeyEvent {
                 code: Key::Enter, ..
             }) => {
-                if let Some(AttrValue::Table(table)) = self.query(Attribute::Content) {
-                    if let Some(row) = table.get(self.component.states.list_index) {
-                        if let Some(selected) = row.first() {
-                            return Some(TuiMsg::AvailableTablesSelected(selected.content.clone()));
-                        }
-                    }
+                if let Some(AttrValue::Table(table)) = self.query(Attribute::Content)
+                    && let Some(row) = table.get(self.component.states.list_index)
+                    && let Some(selected) = row.first()
+                {
+                    return Some(TuiMsg::AvailableTablesSelected(selected.content.clone()));
                 }
                 CmdResult::None
             }
@@ -69,14 +68,11 @@ impl Component<TuiMsg, TuiUserEvent> for ComponentAvailableTables {
                 code: Key::Char('t'),
                 modifiers: KeyModifiers::CONTROL,
             }) => {
-                if let Some(AttrValue::Table(table)) = self.query(Attribute::Content) {
-                    if let Some(row) = table.get(self.component.states.list_index) {
-                        if let Some(selected) = row.first() {
-                            return Some(TuiMsg::DatabaseRequestTableInfo(
-                                selected.content.clone(),
-                            ));
-                        }
-                    }
+                if let Some(AttrValue::Table(table)) = self.query(Attribute::Content)
+                    && let Some(row) = table.get(self.component.states.list_index)
+                    && let Some(selected) = row.first()
+                {
+                    return Some(TuiMsg::DatabaseRequestTableInfo(selected.content.clone()));
                 }
                 CmdResult::None
             }
@@ -95,12 +95,11 @@ impl Component<TuiMsg, TuiUserEvent> for ComponentTableInfoDialog {
             Event::Keyboard(KeyEvent {
                 code: Key::Enter, ..
             }) => {
-                if let Some(AttrValue::Table(table)) = self.query(Attribute::Content) {
-                    if let Some(row) = table.get(self.component.states.list_index) {
-                        if let Some(selected) = row.first() {
-                            return Some(TuiMsg::TableInfoColumnSelected(selected.content.clone()));
-                        }
-                    }
+                if let Some(AttrValue::Table(table)) = self.query(Attribute::Content)
+                    && let Some(row) = table.get(self.component.states.list_index)
+                    && let Some(selected) = row.first()
+                {
+                    return Some(TuiMsg::TableInfoColumnSelected(selected.content.clone()));
                 }
                 CmdResult::None
             }
@@ -30,8 +30,11 @@ impl DatabaseEventListener {
             }
             let row_lower_limit = current_page_index.saturating_mul(page_size);
 
-            let partial_rows: Vec<Vec<String>> =
-                query_result.rows[row_lower_limit..row_upper_limit].to_vec();
+            let partial_rows: Vec<Vec<String>> = query_result.rows
+                [row_lower_limit..row_upper_limit]
+                .iter()
+                .map(|row| row.iter().map(|value| value.to_string()).collect())
+                .collect();
 
             let total_columns = query_result.rows.first().map(|first| first.len());
 
@@ -1,3 +1,4 @@
+use crate::args_util::sql_script::ScriptStatement;
 use crate::db::execute_statements::execute_statements;
 use crate::tui::component_message_dialog::ComponentMessageDialogType;
 use crate::tui::database_event_listener::{DatabaseEventListener, IS_QUERYING, QUERY_RESULT};
@@ -36,7 +37,8 @@ impl DatabaseEventListener {
                 let raw_id = self.args.raw_id.clone();
                 let sender_user_event = self.sender_user_event.clone();
                 std::thread::spawn(move || {
-                    match execute_statements(&pool, statements, Some(raw_id)) {
+                    let statements = statements.into_iter().map(ScriptStatement::from).collect();
+                    match execute_statements(&pool, statements, Some(raw_id), None) {
                         Ok(result) => {
                             let last = result.last().unwrap();
                             {
@@ -1,4 +1,4 @@
-use crate::csv::csv_writer::CsvWriter;
+use crate::output::result_writer::{create_result_writer, write_statement_result};
 use crate::tui::component_message_dialog::ComponentMessageDialogType;
 use crate::tui::database_event_listener::{DatabaseEventListener, IS_SAVING, QUERY_RESULT};
 use crate::tui::tui_id::TuiId;
@@ -10,7 +10,7 @@ impl DatabaseEventListener {
     /// Saves the SQL query result to a specified file.
     ///
     /// # Behavior
-    /// - Determines file format (TSV/CSV) based on the extension.
+    /// - Determines file format (CSV/TSV/JSON/NDJSON) based on the extension.
     /// - Displays a progress dialog during the save operation.
     /// - Shows success or error messages based on the outcome.
     ///
@@ -26,6 +26,10 @@ impl DatabaseEventListener {
 
         let mut args = self.args.clone();
         args.out_file = Some(filename.clone());
+        // Detect the format and compression from the file extension
+        args.out_format = None;
+        args.out_compress = None;
+        args.out_all_results = false;
         let sender_user_event = self.sender_user_event.clone();
 
         std::thread::spawn(move || {
@@ -44,17 +48,10 @@ impl DatabaseEventListener {
                     )))
                     .expect("Failed to send empty result message");
             } else {
-                match CsvWriter::new(&args) {
+                match create_result_writer(&args) {
                     Ok(mut writer) => {
-                        if !args.out_without_header {
-                            writer
-                                .write_record(&query_result_read.header)
-                                .expect("Failed to write headers");
-                        }
-                        writer
-                            .write_records(&query_result_read.rows)
+                        write_statement_result(writer.as_mut(), &query_result_read)
                             .expect("Failed to write rows");
-                        writer.flush().expect("Failed to flush writer");
 
                         sender_user_event
                             .send(Event::User(TuiUserEvent::MessageDialogShow(
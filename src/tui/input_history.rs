@@ -77,8 +77,7 @@ impl InputHistory {
 
     /// Retrieves the current entry at the cursor position.
     fn get_data(&self) -> Option<&String> {
-        let result = self.data.get(self.cursor);
-        result
+        self.data.get(self.cursor)
     }
 
     /// Moves the cursor to the latest entry in the history.
@@ -1,7 +1,7 @@
 use crate::tui::tui_id::TuiId;
 use crate::tui::tui_model::TuiModel;
-use tuirealm::terminal::TerminalAdapter;
 use tuirealm::Attribute;
+use tuirealm::terminal::TerminalAdapter;
 
 impl<T> TuiModel<T>
 where
@@ -17,7 +17,7 @@ where
         components.into_iter().find(|&tui_id| {
             self.app
                 .query(&tui_id, Attribute::Focus)
-                .map_or(false, |attr| attr.map_or(false, |v| v.unwrap_flag()))
+                .is_ok_and(|attr| attr.is_some_and(|v| v.unwrap_flag()))
         })
     }
 }